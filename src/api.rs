@@ -0,0 +1,353 @@
+//! Static JSON API served alongside the Fumadocs pages.
+//!
+//! Third-party tools can consume the plan data over plain HTTP from static hosting.
+//! The tree under `public/api/v1/` is produced from the same models as the MDX:
+//!
+//! - `years.json`: every enrollment year
+//! - `years/<year>/majors.json`: the plans of a year
+//! - `plans/<year>/<major>.json`: a plan with its courses
+//! - `courses/<CODE>.json`: a course with the plans including it and its file tree
+
+use crate::error::Result;
+use crate::generator::{course_page_href, has_course_page, index_occurrences};
+use crate::models::{Course, FileNode, Plan};
+use crate::tree::FileTrees;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::path::Path;
+
+/// Site path of the API root
+pub const API_BASE: &str = "/api/v1";
+
+#[derive(Debug, Serialize)]
+pub struct YearsDocument {
+    years: Vec<YearEntry>,
+}
+
+#[derive(Debug, Serialize)]
+struct YearEntry {
+    year: String,
+    major_count: usize,
+    href: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MajorsDocument<'a> {
+    year: &'a str,
+    majors: Vec<MajorEntry<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct MajorEntry<'a> {
+    major_code: &'a str,
+    major_name: &'a str,
+    course_count: usize,
+    total_credit: f64,
+    href: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlanDocument<'a> {
+    year: &'a str,
+    major_code: &'a str,
+    major_name: &'a str,
+    courses: Vec<PlanCourse<'a>>,
+}
+
+/// A course of a plan, linking to the course document
+#[derive(Debug, Serialize)]
+struct PlanCourse<'a> {
+    #[serde(flatten)]
+    course: &'a Course,
+    href: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CourseDocument<'a> {
+    code: &'a str,
+    /// Name in the most recent plan including the course
+    name: &'a str,
+    /// Site path of the course page, if the course has one
+    page: Option<String>,
+    plans: Vec<CoursePlan<'a>>,
+    files: &'a [FileNode],
+}
+
+/// A plan including a course, with the plan-specific course metadata
+#[derive(Debug, Serialize)]
+struct CoursePlan<'a> {
+    year: &'a str,
+    major_code: &'a str,
+    major_name: &'a str,
+    #[serde(flatten)]
+    course: &'a Course,
+    href: String,
+}
+
+fn plan_href(year: &str, major_code: &str) -> String {
+    format!("{}/plans/{}/{}.json", API_BASE, year, major_code)
+}
+
+fn course_href(code: &str) -> String {
+    format!("{}/courses/{}.json", API_BASE, code)
+}
+
+/// Group plans by year, each year ordered by major code
+pub fn plans_by_year(plans: &[Plan]) -> BTreeMap<&str, Vec<&Plan>> {
+    let mut by_year: BTreeMap<&str, Vec<&Plan>> = BTreeMap::new();
+    for plan in plans {
+        by_year.entry(plan.year.as_str()).or_default().push(plan);
+    }
+    for year_plans in by_year.values_mut() {
+        year_plans.sort_by(|a, b| a.major_code.cmp(&b.major_code));
+    }
+    by_year
+}
+
+/// Every enrollment year, most recent first
+pub fn years_document(by_year: &BTreeMap<&str, Vec<&Plan>>) -> YearsDocument {
+    YearsDocument {
+        years: by_year
+            .iter()
+            .rev()
+            .map(|(year, year_plans)| YearEntry {
+                year: year.to_string(),
+                major_count: year_plans.len(),
+                href: format!("{}/years/{}/majors.json", API_BASE, year),
+            })
+            .collect(),
+    }
+}
+
+/// The plans of a year
+pub fn majors_document<'a>(year: &'a str, year_plans: &[&'a Plan]) -> MajorsDocument<'a> {
+    MajorsDocument {
+        year,
+        majors: year_plans
+            .iter()
+            .map(|plan| MajorEntry {
+                major_code: &plan.major_code,
+                major_name: &plan.major_name,
+                course_count: plan.courses.len(),
+                total_credit: plan.courses.iter().filter_map(|c| c.credit).sum(),
+                href: plan_href(&plan.year, &plan.major_code),
+            })
+            .collect(),
+    }
+}
+
+/// A plan with the given courses, usually all of its courses
+pub fn plan_document<'a, I: IntoIterator<Item = &'a Course>>(
+    plan: &'a Plan,
+    courses: I,
+) -> PlanDocument<'a> {
+    PlanDocument {
+        year: &plan.year,
+        major_code: &plan.major_code,
+        major_name: &plan.major_name,
+        courses: courses
+            .into_iter()
+            .map(|course| PlanCourse {
+                course,
+                href: course_href(&course.code),
+            })
+            .collect(),
+    }
+}
+
+/// A course with every plan including it, most recent first, and its file tree.
+///
+/// `occurrences` are the plans including the course, as indexed by
+/// [`index_occurrences`]. Returns None if there are none.
+pub fn course_document<'a>(
+    code: &'a str,
+    occurrences: &[(&'a Plan, &'a Course)],
+    repos_dir: &Path,
+    repos_set: &HashSet<String>,
+    trees: &'a FileTrees,
+) -> Option<CourseDocument<'a>> {
+    let mut course_plans = occurrences.to_vec();
+    course_plans.sort_by(|(a, _), (b, _)| a.cmp_recency(b));
+
+    let (_, latest) = course_plans.first()?;
+
+    Some(CourseDocument {
+        code,
+        name: &latest.name,
+        page: has_course_page(code, repos_dir, repos_set).then(|| course_page_href(code)),
+        plans: course_plans
+            .iter()
+            .map(|(plan, course)| CoursePlan {
+                year: &plan.year,
+                major_code: &plan.major_code,
+                major_name: &plan.major_name,
+                course,
+                href: plan_href(&plan.year, &plan.major_code),
+            })
+            .collect(),
+        files: trees.get(code).map_or(&[], |tree| &tree.nodes),
+    })
+}
+
+/// Write a document as pretty-printed JSON, creating parent directories
+fn write_json<T: Serialize>(path: &Path, document: &T) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(document)?)?;
+    Ok(())
+}
+
+/// Generate the static JSON API under `public_dir/api/v1/`.
+pub fn generate_api(
+    plans: &[Plan],
+    repos_dir: &Path,
+    repos_set: &HashSet<String>,
+    trees: &FileTrees,
+    public_dir: &Path,
+) -> Result<()> {
+    let api_dir = public_dir.join(API_BASE.trim_start_matches('/'));
+    let by_year = plans_by_year(plans);
+
+    write_json(&api_dir.join("years.json"), &years_document(&by_year))?;
+
+    for (year, year_plans) in &by_year {
+        write_json(
+            &api_dir.join("years").join(year).join("majors.json"),
+            &majors_document(year, year_plans),
+        )?;
+
+        for plan in year_plans {
+            write_json(
+                &api_dir
+                    .join("plans")
+                    .join(&plan.year)
+                    .join(format!("{}.json", plan.major_code)),
+                &plan_document(plan, &plan.courses),
+            )?;
+        }
+    }
+
+    let codes: BTreeSet<&str> = plans
+        .iter()
+        .flat_map(|plan| &plan.courses)
+        .map(|course| course.code.as_str())
+        .collect();
+
+    generate_course_documents(codes, plans, repos_dir, repos_set, trees, public_dir)
+}
+
+/// Write `courses/<CODE>.json` of the given courses only.
+///
+/// Codes that no plan includes are skipped.
+pub fn generate_course_documents<'a, I: IntoIterator<Item = &'a str>>(
+    codes: I,
+    plans: &[Plan],
+    repos_dir: &Path,
+    repos_set: &HashSet<String>,
+    trees: &FileTrees,
+    public_dir: &Path,
+) -> Result<()> {
+    let courses_dir = public_dir
+        .join(API_BASE.trim_start_matches('/'))
+        .join("courses");
+
+    let occurrences = index_occurrences(plans);
+    for code in codes {
+        let Some(occurrences) = occurrences.get(code) else {
+            continue;
+        };
+        if let Some(document) = course_document(code, occurrences, repos_dir, repos_set, trees) {
+            write_json(&courses_dir.join(format!("{}.json", code)), &document)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ignore::IgnoreRules;
+    use crate::loader::load_file_trees;
+    use crate::models::test_support::plan;
+
+    fn course(code: &str, name: &str) -> Course {
+        crate::models::test_support::course(code)
+            .with_name(name)
+            .with_credit(2.0)
+    }
+
+    fn read_json(path: &Path) -> serde_json::Value {
+        serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_generate_api() {
+        let temp_dir = std::env::temp_dir().join("test_generate_api");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let repos_dir = temp_dir.join("repos");
+        let public_dir = temp_dir.join("public");
+        fs::create_dir_all(&repos_dir).unwrap();
+        fs::write(repos_dir.join("MATH1001.mdx"), "# 高等数学").unwrap();
+        fs::write(
+            repos_dir.join("MATH1001.json"),
+            r#"{"notes/a.pdf": {"size": 10, "time": null}}"#,
+        )
+        .unwrap();
+
+        let plans = vec![
+            plan("2022", "080901", vec![course("MATH1001", "旧名")]),
+            plan(
+                "2023",
+                "080902",
+                vec![
+                    course("MATH1001", "高等数学"),
+                    course("PHYS1001", "大学物理"),
+                ],
+            ),
+            plan("2023", "080901", vec![]),
+        ];
+        let trees = load_file_trees(&plans, &repos_dir, &IgnoreRules::default()).unwrap();
+        generate_api(&plans, &repos_dir, &HashSet::new(), &trees, &public_dir).unwrap();
+        let api_dir = public_dir.join("api/v1");
+
+        let years = read_json(&api_dir.join("years.json"));
+        assert_eq!(years["years"][0]["year"], "2023");
+        assert_eq!(years["years"][0]["major_count"], 2);
+        assert_eq!(years["years"][1]["href"], "/api/v1/years/2022/majors.json");
+
+        let majors = read_json(&api_dir.join("years/2023/majors.json"));
+        assert_eq!(majors["majors"][0]["major_code"], "080901");
+        assert_eq!(majors["majors"][1]["course_count"], 2);
+        assert_eq!(majors["majors"][1]["total_credit"], 4.0);
+        assert_eq!(
+            majors["majors"][1]["href"],
+            "/api/v1/plans/2023/080902.json"
+        );
+
+        let plan_doc = read_json(&api_dir.join("plans/2023/080902.json"));
+        assert_eq!(plan_doc["courses"][0]["code"], "MATH1001");
+        assert_eq!(
+            plan_doc["courses"][0]["href"],
+            "/api/v1/courses/MATH1001.json"
+        );
+
+        let math = read_json(&api_dir.join("courses/MATH1001.json"));
+        assert_eq!(math["name"], "高等数学");
+        assert_eq!(math["page"], "/docs/courses/MATH1001");
+        assert_eq!(math["plans"][0]["year"], "2023");
+        assert_eq!(math["plans"][1]["year"], "2022");
+        assert_eq!(math["files"][0]["name"], "notes");
+        assert_eq!(math["files"][0]["type"], "folder");
+        assert_eq!(math["files"][0]["children"][0]["type"], "file");
+        assert_eq!(math["files"][0]["children"][0]["size"], 10);
+
+        let physics = read_json(&api_dir.join("courses/PHYS1001.json"));
+        assert!(physics["page"].is_null());
+        assert_eq!(physics["files"], serde_json::json!([]));
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+}
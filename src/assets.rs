@@ -0,0 +1,315 @@
+//! Local mirroring of README images.
+//!
+//! Images in course READMEs are hotlinked from GitHub, which is slow or blocked for
+//! many users. This module discovers image references in page content, downloads
+//! them into a content-addressed directory under `public/`, and rewrites the
+//! references to point at the local copies.
+//!
+//! A manifest maps every mirrored source URL to its file name, so subsequent runs
+//! only download images that have not been mirrored yet.
+
+use crate::error::Result;
+use crate::fetcher;
+use futures::stream::{self, StreamExt};
+use regex::{Captures, Regex};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// Number of images downloaded at the same time
+pub const MIRROR_CONCURRENCY: usize = 20;
+
+/// Directory under `public/` holding mirrored assets
+const ASSETS_DIR: &str = "assets";
+
+/// Manifest file name inside the assets directory
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Extensions of the mirrored raster images, see [`sniff_image_extension`]
+const RASTER_EXTENSIONS: &[&str] = &["png", "jpg", "gif", "webp", "bmp", "avif"];
+
+/// Markdown image with a remote source, capturing the prefix and the URL
+static MARKDOWN_IMAGE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(!\[[^\]]*\]\(\s*<?)(https?://[^\s)>]+)").unwrap());
+
+/// `<img>` tag with a remote source, capturing the prefix and the URL
+static HTML_IMAGE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(<img\b[^>]*?\ssrc=["'])(https?://[^"']+)"#).unwrap());
+
+/// Content-addressed mirror of remote images
+pub struct AssetMirror {
+    assets_dir: PathBuf,
+    /// Source URL -> mirrored file name
+    manifest: BTreeMap<String, String>,
+}
+
+impl AssetMirror {
+    /// Open the mirror under `public_dir`, loading the manifest of a previous run.
+    ///
+    /// A missing or unreadable manifest starts an empty mirror. Entries that are
+    /// not raster images, left by earlier versions, are dropped so their pages
+    /// link to the original URL again.
+    pub fn load(public_dir: &Path) -> Result<Self> {
+        let assets_dir = public_dir.join(ASSETS_DIR);
+        fs::create_dir_all(&assets_dir)?;
+
+        let mut manifest: BTreeMap<String, String> =
+            fs::read_to_string(assets_dir.join(MANIFEST_FILE))
+                .ok()
+                .and_then(|content| serde_json::from_str(&content).ok())
+                .unwrap_or_default();
+        manifest.retain(|_, name| {
+            name.rsplit_once('.')
+                .is_some_and(|(_, ext)| RASTER_EXTENSIONS.contains(&ext))
+        });
+
+        Ok(Self {
+            assets_dir,
+            manifest,
+        })
+    }
+
+    /// Check whether a URL has been mirrored and its file is still present
+    fn is_mirrored(&self, url: &str) -> bool {
+        self.manifest
+            .get(url)
+            .is_some_and(|name| self.assets_dir.join(name).exists())
+    }
+
+    /// Download all images not mirrored yet.
+    ///
+    /// Failed downloads are reported and skipped; their references keep pointing at
+    /// the original URL. Returns the number of newly mirrored images.
+    pub async fn mirror_all(&mut self, urls: &[String], concurrency: usize) -> Result<usize> {
+        let pending: Vec<&String> = urls
+            .iter()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .filter(|url| !self.is_mirrored(url))
+            .collect();
+
+        if pending.is_empty() {
+            return Ok(0);
+        }
+
+        println!("Mirroring {} images...", pending.len());
+
+        let client = fetcher::asset_client()?;
+        let results: Vec<_> = stream::iter(pending)
+            .map(|url| {
+                let client = &client;
+                async move { (url, fetcher::fetch_asset(client, url).await) }
+            })
+            .buffer_unordered(concurrency)
+            .collect()
+            .await;
+
+        let mut mirrored = 0;
+        for (url, result) in results {
+            let bytes = match result {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    eprintln!("Warning: Failed to mirror {}: {}", url, e);
+                    continue;
+                }
+            };
+
+            let Some(name) = asset_file_name(&bytes) else {
+                eprintln!(
+                    "Warning: {} is not a recognised raster image, keeping the original URL",
+                    url
+                );
+                continue;
+            };
+
+            // Identical content from another URL is already on disk
+            let path = self.assets_dir.join(&name);
+            if !path.exists() {
+                fs::write(&path, &bytes)?;
+            }
+
+            self.manifest.insert(url.clone(), name);
+            mirrored += 1;
+        }
+
+        Ok(mirrored)
+    }
+
+    /// Rewrite mirrored image references in content to their local paths
+    pub fn rewrite(&self, content: &str) -> String {
+        let replace = |caps: &Captures| {
+            let url = &caps[2];
+            match self.manifest.get(url) {
+                Some(name) if self.assets_dir.join(name).exists() => {
+                    format!("{}/{}/{}", &caps[1], ASSETS_DIR, name)
+                }
+                _ => caps[0].to_string(),
+            }
+        };
+
+        let result = MARKDOWN_IMAGE_RE.replace_all(content, replace).to_string();
+        HTML_IMAGE_RE.replace_all(&result, replace).to_string()
+    }
+
+    /// Persist the manifest for the next run
+    pub fn save(&self) -> Result<()> {
+        fs::write(
+            self.assets_dir.join(MANIFEST_FILE),
+            serde_json::to_string_pretty(&self.manifest)?,
+        )?;
+        Ok(())
+    }
+}
+
+/// Discover remote image URLs referenced in content.
+///
+/// Covers Markdown images and `<img>` tags. Shield badges are skipped since the
+/// formatter strips them anyway.
+pub fn discover_images(content: &str) -> Vec<String> {
+    MARKDOWN_IMAGE_RE
+        .captures_iter(content)
+        .chain(HTML_IMAGE_RE.captures_iter(content))
+        .map(|caps| caps[2].to_string())
+        .filter(|url| !url.contains("img.shields.io"))
+        .collect()
+}
+
+/// Content-addressed file name for a downloaded image.
+///
+/// The extension comes from the file signature, whatever the URL says. Returns
+/// None for content that is not a raster image: mirrored files are served from the
+/// site's own origin, so HTML error pages and SVGs (which may carry scripts) are
+/// never stored.
+fn asset_file_name(bytes: &[u8]) -> Option<String> {
+    let extension = sniff_image_extension(bytes)?;
+
+    let digest = Sha256::digest(bytes);
+    let hash: String = digest[..8].iter().map(|b| format!("{:02x}", b)).collect();
+
+    Some(format!("{}.{}", hash, extension))
+}
+
+/// Detect the raster image type from its leading bytes
+fn sniff_image_extension(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG") {
+        Some("png")
+    } else if bytes.starts_with(b"\xff\xd8\xff") {
+        Some("jpg")
+    } else if bytes.starts_with(b"GIF8") {
+        Some("gif")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("webp")
+    } else if bytes.starts_with(b"BM") {
+        Some("bmp")
+    } else if bytes.len() >= 12
+        && &bytes[4..8] == b"ftyp"
+        && matches!(&bytes[8..12], b"avif" | b"avis")
+    {
+        Some("avif")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discover_images() {
+        let content = r#"![a](https://example.com/a.png "t")
+<img src="https://example.com/b.jpg" width="50%">
+![badge](https://img.shields.io/badge/x)
+![local](/assets/c.png)
+[not an image](https://example.com/d.png)"#;
+
+        let urls = discover_images(content);
+        assert_eq!(
+            urls,
+            vec!["https://example.com/a.png", "https://example.com/b.jpg"]
+        );
+    }
+
+    #[test]
+    fn test_asset_file_name_is_content_addressed() {
+        let a = asset_file_name(b"\x89PNG same").unwrap();
+        let b = asset_file_name(b"\x89PNG same").unwrap();
+        assert_eq!(a, b);
+        assert!(a.ends_with(".png"));
+        assert_eq!(a.len(), 16 + ".png".len());
+
+        let c = asset_file_name(b"\x89PNG different").unwrap();
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn test_asset_file_name_sniffs_extension() {
+        assert!(asset_file_name(b"\x89PNG\r\n").unwrap().ends_with(".png"));
+        assert!(asset_file_name(b"\xff\xd8\xff\xe0")
+            .unwrap()
+            .ends_with(".jpg"));
+        assert!(asset_file_name(b"\0\0\0\x1cftypavif")
+            .unwrap()
+            .ends_with(".avif"));
+
+        // HTML served for a `.png` URL and SVGs are not mirrored
+        assert!(asset_file_name(b"<html>").is_none());
+        assert!(asset_file_name(b"<svg onload=\"alert(1)\">").is_none());
+        assert!(asset_file_name(b"<?xml version=\"1.0\"?><svg/>").is_none());
+    }
+
+    #[test]
+    fn test_load_drops_non_raster_entries() {
+        let temp_dir = std::env::temp_dir().join("test_asset_mirror_load");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(temp_dir.join("assets")).unwrap();
+        fs::write(
+            temp_dir.join("assets/manifest.json"),
+            r#"{"https://example.com/a.png": "0011223344556677.png", "https://example.com/b.svg": "8899aabbccddeeff.svg"}"#,
+        )
+        .unwrap();
+
+        let mirror = AssetMirror::load(&temp_dir).unwrap();
+        assert_eq!(
+            mirror.manifest.keys().collect::<Vec<_>>(),
+            vec!["https://example.com/a.png"]
+        );
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_rewrite_uses_manifest() {
+        let temp_dir = std::env::temp_dir().join("test_asset_mirror_rewrite");
+        let _ = fs::remove_dir_all(&temp_dir);
+
+        let mut mirror = AssetMirror::load(&temp_dir).unwrap();
+        mirror.manifest.insert(
+            "https://example.com/a.png".to_string(),
+            "0011223344556677.png".to_string(),
+        );
+        mirror.manifest.insert(
+            "https://example.com/missing.png".to_string(),
+            "8899aabbccddeeff.png".to_string(),
+        );
+        fs::write(temp_dir.join("assets/0011223344556677.png"), b"x").unwrap();
+
+        let content = r#"![a](https://example.com/a.png) <img src="https://example.com/a.png"> ![m](https://example.com/missing.png)"#;
+        let output = mirror.rewrite(content);
+
+        assert_eq!(
+            output,
+            r#"![a](/assets/0011223344556677.png) <img src="/assets/0011223344556677.png"> ![m](https://example.com/missing.png)"#
+        );
+        assert!(mirror.is_mirrored("https://example.com/a.png"));
+        assert!(!mirror.is_mirrored("https://example.com/missing.png"));
+
+        mirror.save().unwrap();
+        let reloaded = AssetMirror::load(&temp_dir).unwrap();
+        assert_eq!(reloaded.manifest.len(), 2);
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+}
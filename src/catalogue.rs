@@ -0,0 +1,291 @@
+//! Global catalogue of every course covered by the site.
+//!
+//! The catalogue lists each unique course code across all plans with its credit
+//! range, course natures, the plans including it and the state of its repository
+//! (README, resource files). It is written as the index page of `courses/` and as
+//! `public/data/courses.json`.
+
+use crate::error::Result;
+use crate::generator::{course_page_href, escape_table_cell, format_credit_range, has_course_page};
+use crate::models::{latest_course_names, Plan};
+use crate::tree::{format_size, summarize_files, FileTrees};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+/// A course in the catalogue
+#[derive(Debug, Serialize)]
+pub struct CatalogueEntry {
+    pub code: String,
+    /// Name in the most recent plan including the course
+    pub name: String,
+    pub credit_min: Option<f64>,
+    pub credit_max: Option<f64>,
+    pub natures: Vec<String>,
+    pub years: Vec<String>,
+    pub plans: Vec<PlanRef>,
+    /// Whether the course has a generated course page
+    pub has_page: bool,
+    pub has_readme: bool,
+    pub has_resources: bool,
+    pub file_count: usize,
+    pub total_size: u64,
+}
+
+/// A plan including a course
+#[derive(Debug, Serialize)]
+pub struct PlanRef {
+    pub year: String,
+    pub major_code: String,
+    pub major_name: String,
+}
+
+/// JSON artifact of the catalogue
+#[derive(Debug, Serialize)]
+struct Catalogue<'a> {
+    total: usize,
+    courses: &'a [CatalogueEntry],
+}
+
+/// Build the catalogue entries, sorted by course code
+pub fn build_catalogue(
+    plans: &[Plan],
+    repos_dir: &Path,
+    repos_set: &HashSet<String>,
+    trees: &FileTrees,
+) -> Vec<CatalogueEntry> {
+    let names = latest_course_names(plans);
+    let mut ordered: Vec<&Plan> = plans.iter().collect();
+    ordered.sort_by(|a, b| a.cmp_recency(b));
+
+    let mut entries: BTreeMap<&str, CatalogueEntry> = BTreeMap::new();
+    for plan in ordered {
+        for course in &plan.courses {
+            let entry = entries
+                .entry(course.code.as_str())
+                .or_insert_with(|| CatalogueEntry {
+                    code: course.code.clone(),
+                    name: names[course.code.as_str()].to_string(),
+                    credit_min: None,
+                    credit_max: None,
+                    natures: Vec::new(),
+                    years: Vec::new(),
+                    plans: Vec::new(),
+                    has_page: false,
+                    has_readme: false,
+                    has_resources: false,
+                    file_count: 0,
+                    total_size: 0,
+                });
+
+            if let Some(credit) = course.credit {
+                entry.credit_min = Some(entry.credit_min.map_or(credit, |c| c.min(credit)));
+                entry.credit_max = Some(entry.credit_max.map_or(credit, |c| c.max(credit)));
+            }
+            if let Some(nature) = course.course_nature.as_deref().map(str::trim) {
+                if !nature.is_empty() && !entry.natures.iter().any(|n| n == nature) {
+                    entry.natures.push(nature.to_string());
+                }
+            }
+            if !entry.years.contains(&plan.year) {
+                entry.years.push(plan.year.clone());
+            }
+            if !entry
+                .plans
+                .iter()
+                .any(|p| p.year == plan.year && p.major_code == plan.major_code)
+            {
+                entry.plans.push(PlanRef {
+                    year: plan.year.clone(),
+                    major_code: plan.major_code.clone(),
+                    major_name: plan.major_name.clone(),
+                });
+            }
+        }
+    }
+
+    let mut catalogue: Vec<CatalogueEntry> = entries.into_values().collect();
+    for entry in &mut catalogue {
+        entry.years.sort();
+        entry.has_page = has_course_page(&entry.code, repos_dir, repos_set);
+        entry.has_readme = repos_dir.join(format!("{}.mdx", entry.code)).exists();
+
+        if let Some(tree) = trees.get(&entry.code) {
+            let (file_count, total_size) = summarize_files(&tree.nodes);
+            entry.file_count = file_count;
+            entry.total_size = total_size;
+        }
+        entry.has_resources = entry.file_count > 0;
+    }
+
+    catalogue
+}
+
+/// Build the catalogue page listing every course
+pub fn build_catalogue_page(entries: &[CatalogueEntry]) -> String {
+    let with_resources = entries.iter().filter(|e| e.has_resources).count();
+    let summary = format!(
+        "共 {} 门课程，其中 {} 门有资料",
+        entries.len(),
+        with_resources
+    );
+
+    let mut lines = vec![
+        "---".to_string(),
+        "title: 课程目录".to_string(),
+        format!("description: {}", summary),
+        "---".to_string(),
+        "".to_string(),
+        format!("{}。", summary),
+        "".to_string(),
+        "| 课程代码 | 课程名称 | 学分 | 课程性质 | 开设年级 | 资料 |".to_string(),
+        "| --- | --- | --- | --- | --- | --- |".to_string(),
+    ];
+
+    for entry in entries {
+        let name = if entry.name.trim().is_empty() {
+            &entry.code
+        } else {
+            &entry.name
+        };
+        let name = if entry.has_page {
+            format!(
+                "[{}]({})",
+                escape_table_cell(name),
+                course_page_href(&entry.code)
+            )
+        } else {
+            escape_table_cell(name)
+        };
+        let natures = if entry.natures.is_empty() {
+            "—".to_string()
+        } else {
+            entry.natures.join("、")
+        };
+        let resources = if entry.has_resources {
+            format!(
+                "{} 个文件 · {}",
+                entry.file_count,
+                format_size(entry.total_size)
+            )
+        } else {
+            "—".to_string()
+        };
+
+        lines.push(format!(
+            "| {} | {} | {} | {} | {} | {} |",
+            entry.code,
+            name,
+            format_credit_range([entry.credit_min, entry.credit_max]),
+            escape_table_cell(&natures),
+            entry.years.join("、"),
+            resources,
+        ));
+    }
+
+    lines.join("\n")
+}
+
+/// Generate `courses/index.mdx` and `public/data/courses.json`
+pub fn generate_catalogue(
+    plans: &[Plan],
+    repos_dir: &Path,
+    repos_set: &HashSet<String>,
+    trees: &FileTrees,
+    docs_dir: &Path,
+    public_dir: &Path,
+) -> Result<()> {
+    let entries = build_catalogue(plans, repos_dir, repos_set, trees);
+
+    let courses_dir = docs_dir.join("courses");
+    fs::create_dir_all(&courses_dir)?;
+    fs::write(
+        courses_dir.join("index.mdx"),
+        build_catalogue_page(&entries),
+    )?;
+
+    let data_dir = public_dir.join("data");
+    fs::create_dir_all(&data_dir)?;
+    let catalogue = Catalogue {
+        total: entries.len(),
+        courses: &entries,
+    };
+    fs::write(
+        data_dir.join("courses.json"),
+        serde_json::to_string_pretty(&catalogue)?,
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ignore::IgnoreRules;
+    use crate::loader::load_file_trees;
+    use crate::models::test_support::plan;
+    use crate::models::Course;
+
+    fn course(code: &str, name: &str, credit: f64, nature: &str) -> Course {
+        crate::models::test_support::course(code)
+            .with_name(name)
+            .with_credit(credit)
+            .with_nature(nature)
+    }
+
+    #[test]
+    fn test_build_catalogue() {
+        let temp_dir = std::env::temp_dir().join("test_build_catalogue");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        fs::write(temp_dir.join("MATH1001.mdx"), "# 高等数学").unwrap();
+        fs::write(
+            temp_dir.join("MATH1001.json"),
+            r#"{"a.pdf": {"size": 1024, "time": null}, "README.md": {"size": 10, "time": null}, "b/c.pdf": {"size": 512, "time": null}}"#,
+        )
+        .unwrap();
+
+        let plans = vec![
+            plan(
+                "2022",
+                "080901",
+                vec![course("MATH1001", "旧名", 5.0, "必修")],
+            ),
+            plan(
+                "2023",
+                "080902",
+                vec![
+                    course("MATH1001", "高等数学", 5.5, "限选"),
+                    course("PHYS1001", "大学物理", 4.0, "必修"),
+                ],
+            ),
+        ];
+
+        let trees = load_file_trees(&plans, &temp_dir, &IgnoreRules::builtin()).unwrap();
+        let entries = build_catalogue(&plans, &temp_dir, &HashSet::new(), &trees);
+        assert_eq!(entries.len(), 2);
+
+        let math = &entries[0];
+        assert_eq!(math.code, "MATH1001");
+        assert_eq!(math.name, "高等数学");
+        assert_eq!((math.credit_min, math.credit_max), (Some(5.0), Some(5.5)));
+        assert_eq!(math.natures, vec!["限选", "必修"]);
+        assert_eq!(math.years, vec!["2022", "2023"]);
+        assert_eq!(math.plans.len(), 2);
+        assert!(math.has_page && math.has_readme && math.has_resources);
+        assert_eq!((math.file_count, math.total_size), (2, 1536));
+
+        let physics = &entries[1];
+        assert!(!physics.has_page && !physics.has_readme && !physics.has_resources);
+
+        let page = build_catalogue_page(&entries);
+        assert!(page.contains("description: 共 2 门课程，其中 1 门有资料"));
+        assert!(page.contains(
+            "| MATH1001 | [高等数学](/docs/courses/MATH1001) | 5–5.5 | 限选、必修 | 2022、2023 | 2 个文件 · 1.5 KB |"
+        ));
+        assert!(page.contains("| PHYS1001 | 大学物理 | 4 | 必修 | 2023 | — |"));
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+}
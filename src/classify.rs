@@ -0,0 +1,245 @@
+//! Classification of resource files.
+//!
+//! Files are classified by type from their extension, which decides how they are
+//! shown (icon), and by category from keywords in their path, which tells what
+//! they are for (exam, homework...). Both are heuristics on names only.
+
+use serde::Serialize;
+
+/// Format of a file, from its extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileType {
+    Pdf,
+    Slides,
+    Doc,
+    Archive,
+    Code,
+    Image,
+    Video,
+    Other,
+}
+
+/// Extensions of each file type
+const FILE_TYPE_EXTENSIONS: &[(FileType, &[&str])] = &[
+    (FileType::Pdf, &["pdf"]),
+    (FileType::Slides, &["ppt", "pptx", "key", "odp"]),
+    (
+        FileType::Doc,
+        &[
+            "doc", "docx", "odt", "rtf", "txt", "md", "tex", "xls", "xlsx", "ods", "csv", "epub",
+            "djvu", "caj",
+        ],
+    ),
+    (
+        FileType::Archive,
+        &["zip", "rar", "7z", "tar", "gz", "tgz", "bz2", "xz"],
+    ),
+    (
+        FileType::Code,
+        &[
+            "c", "h", "cpp", "hpp", "cc", "py", "ipynb", "java", "js", "ts", "rs", "go", "m",
+            "mat", "v", "sv", "vhd", "asm", "s", "sh", "html", "css", "json", "xml", "sql", "r",
+            "jl",
+        ],
+    ),
+    (
+        FileType::Image,
+        &["png", "jpg", "jpeg", "gif", "webp", "svg", "bmp"],
+    ),
+    (
+        FileType::Video,
+        &["mp4", "mkv", "avi", "mov", "webm", "flv"],
+    ),
+];
+
+/// Type of a file from the extension of its name or path
+pub fn file_type(name: &str) -> FileType {
+    let file_name = name.rsplit('/').next().unwrap_or(name);
+    let Some((_, extension)) = file_name.rsplit_once('.') else {
+        return FileType::Other;
+    };
+    let extension = extension.to_lowercase();
+
+    FILE_TYPE_EXTENSIONS
+        .iter()
+        .find(|(_, extensions)| extensions.contains(&extension.as_str()))
+        .map_or(FileType::Other, |(file_type, _)| *file_type)
+}
+
+/// Kind of content of a resource file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    Exam,
+    Homework,
+    Lab,
+    Slides,
+    Notes,
+    Other,
+}
+
+/// Path keywords of each category, checked in this order. ASCII keywords match
+/// whole words, optionally followed by a number (`hw3`, `lab02`); others match
+/// anywhere.
+const CATEGORY_KEYWORDS: &[(Category, &[&str])] = &[
+    (
+        Category::Exam,
+        &[
+            "试卷", "试题", "真题", "考试", "考题", "期末", "期中", "测验", "exam", "exams",
+            "final", "finals", "midterm", "quiz",
+        ],
+    ),
+    (
+        Category::Homework,
+        &[
+            "作业",
+            "习题",
+            "练习",
+            "homework",
+            "hw",
+            "assignment",
+            "assignments",
+            "exercise",
+            "exercises",
+        ],
+    ),
+    (
+        Category::Lab,
+        &[
+            "实验",
+            "实训",
+            "课设",
+            "课程设计",
+            "lab",
+            "labs",
+            "experiment",
+        ],
+    ),
+    (
+        Category::Slides,
+        &[
+            "课件",
+            "幻灯片",
+            "ppt",
+            "slides",
+            "slide",
+            "lecture",
+            "lectures",
+        ],
+    ),
+    (
+        Category::Notes,
+        &[
+            "笔记",
+            "讲义",
+            "复习",
+            "总结",
+            "提纲",
+            "notes",
+            "note",
+            "summary",
+            "cheatsheet",
+            "review",
+        ],
+    ),
+];
+
+impl Category {
+    /// All categories, in display order
+    pub const ALL: [Category; 6] = [
+        Category::Exam,
+        Category::Homework,
+        Category::Lab,
+        Category::Slides,
+        Category::Notes,
+        Category::Other,
+    ];
+
+    /// Display name of the category
+    pub fn label(self) -> &'static str {
+        match self {
+            Category::Exam => "试卷",
+            Category::Homework => "作业",
+            Category::Lab => "实验",
+            Category::Slides => "课件",
+            Category::Notes => "笔记",
+            Category::Other => "其他",
+        }
+    }
+}
+
+/// Check whether a path segment contains a category keyword
+fn matches_keyword(segment: &str, keyword: &str) -> bool {
+    if !keyword.is_ascii() {
+        return segment.contains(keyword);
+    }
+
+    segment
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .any(|word| {
+            word.strip_prefix(keyword)
+                .is_some_and(|rest| rest.chars().all(|c| c.is_ascii_digit()))
+        })
+}
+
+/// Guess the category of a file from its path.
+///
+/// The file name is checked first, then its folders from the innermost one, so a
+/// `作业答案.pdf` in `课件/` counts as homework. Slide decks without any keyword
+/// count as slides.
+pub fn category(path: &str) -> Category {
+    let lower = path.to_lowercase();
+    let segments: Vec<&str> = lower.split('/').collect();
+
+    for segment in segments.iter().rev() {
+        for (category, keywords) in CATEGORY_KEYWORDS {
+            if keywords.iter().any(|k| matches_keyword(segment, k)) {
+                return *category;
+            }
+        }
+    }
+
+    if file_type(path) == FileType::Slides {
+        return Category::Slides;
+    }
+
+    Category::Other
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_type() {
+        assert_eq!(file_type("notes/ch1.PDF"), FileType::Pdf);
+        assert_eq!(file_type("第三章.pptx"), FileType::Slides);
+        assert_eq!(file_type("报告.docx"), FileType::Doc);
+        assert_eq!(file_type("code.tar.gz"), FileType::Archive);
+        assert_eq!(file_type("src/main.cpp"), FileType::Code);
+        assert_eq!(file_type("img/a.jpeg"), FileType::Image);
+        assert_eq!(file_type("录屏.mp4"), FileType::Video);
+        assert_eq!(file_type("Makefile"), FileType::Other);
+        assert_eq!(file_type("v1.0/data"), FileType::Other);
+    }
+
+    #[test]
+    fn test_category() {
+        assert_eq!(category("往年试卷/2022期末.pdf"), Category::Exam);
+        assert_eq!(category("Final-2021.pdf"), Category::Exam);
+        assert_eq!(category("hw/hw3.pdf"), Category::Homework);
+        assert_eq!(category("作业/第一次作业答案.docx"), Category::Homework);
+        assert_eq!(category("Lab02/report.docx"), Category::Lab);
+        assert_eq!(category("实验/实验报告模板.docx"), Category::Lab);
+        assert_eq!(category("slides/lecture01.pdf"), Category::Slides);
+        assert_eq!(category("第三章.pptx"), Category::Slides);
+        assert_eq!(category("复习提纲.md"), Category::Notes);
+
+        // The file name wins over its folders
+        assert_eq!(category("课件/第二章习题.pdf"), Category::Homework);
+        // ASCII keywords only match whole words
+        assert_eq!(category("show/labels.txt"), Category::Other);
+        assert_eq!(category("教材.pdf"), Category::Other);
+    }
+}
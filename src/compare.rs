@@ -0,0 +1,518 @@
+//! Comparison of training plans across enrollment years.
+//!
+//! For every major with plans in several years, consecutive plans are compared
+//! course by course: added and removed courses, and changes in credit, recommended
+//! semester, course nature and grading scheme. The result is written both as MDX
+//! pages under `content/docs/compare/` and as JSON under `public/data/compare/`.
+
+use crate::config::SemestersConfig;
+use crate::error::Result;
+use crate::generator::{escape_table_cell, format_credit, yaml_string};
+use crate::models::{Course, GradeDetail, Plan};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Difference between the plans of a major in two enrollment years
+#[derive(Debug, Serialize)]
+pub struct PlanDiff {
+    pub from_year: String,
+    pub to_year: String,
+    pub added: Vec<CourseSummary>,
+    pub removed: Vec<CourseSummary>,
+    pub changed: Vec<CourseChange>,
+}
+
+/// A course added to or removed from a plan
+#[derive(Debug, Serialize)]
+pub struct CourseSummary {
+    pub code: String,
+    pub name: String,
+    pub credit: Option<f64>,
+    pub semester: String,
+    pub nature: Option<String>,
+}
+
+/// A course present in both plans whose plan-specific metadata changed
+#[derive(Debug, Serialize)]
+pub struct CourseChange {
+    pub code: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credit: Option<Change<Option<f64>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub semester: Option<Change<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nature: Option<Change<Option<String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grading: Option<Change<Vec<GradeDetail>>>,
+}
+
+/// Old and new value of a changed field
+#[derive(Debug, Serialize)]
+pub struct Change<T> {
+    pub from: T,
+    pub to: T,
+}
+
+/// JSON artifact of all comparisons of a major
+#[derive(Debug, Serialize)]
+struct MajorComparisons<'a> {
+    major_code: &'a str,
+    major_name: &'a str,
+    comparisons: &'a [PlanDiff],
+}
+
+impl PlanDiff {
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Display titles of the semesters of a course, joined
+fn semester_titles(course: &Course, semesters: &SemestersConfig) -> String {
+    semesters
+        .placements(course.recommended_semester.as_deref())
+        .into_iter()
+        .map(|s| s.title)
+        .collect::<Vec<_>>()
+        .join("、")
+}
+
+/// Course nature, treating blank values as missing
+fn nature(course: &Course) -> Option<String> {
+    course
+        .course_nature
+        .as_deref()
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .map(str::to_string)
+}
+
+/// Human-readable grading scheme, e.g. `平时 30%、期末 70%`
+fn format_grading(details: &[GradeDetail]) -> String {
+    if details.is_empty() {
+        return "—".to_string();
+    }
+
+    details
+        .iter()
+        .map(|d| match d.percent.as_deref() {
+            Some(percent) if !percent.is_empty() => {
+                format!("{} {}%", d.name, percent.trim_end_matches('%'))
+            }
+            _ => d.name.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join("、")
+}
+
+fn summarize(course: &Course, semesters: &SemestersConfig) -> CourseSummary {
+    CourseSummary {
+        code: course.code.clone(),
+        name: course.name.clone(),
+        credit: course.credit,
+        semester: semester_titles(course, semesters),
+        nature: nature(course),
+    }
+}
+
+/// Wrap two values in a [`Change`] if they differ
+fn change<T: PartialEq>(from: T, to: T) -> Option<Change<T>> {
+    (from != to).then_some(Change { from, to })
+}
+
+/// Courses of a plan by code, keeping the first occurrence of each code
+fn index_courses(plan: &Plan) -> BTreeMap<&str, &Course> {
+    let mut courses = BTreeMap::new();
+    for course in &plan.courses {
+        courses.entry(course.code.as_str()).or_insert(course);
+    }
+    courses
+}
+
+/// Compare the plans of a major in two enrollment years.
+///
+/// Courses are matched by code; lists are sorted by course code.
+pub fn compare_plans(from: &Plan, to: &Plan, semesters: &SemestersConfig) -> PlanDiff {
+    let old = index_courses(from);
+    let new = index_courses(to);
+
+    let added = new
+        .iter()
+        .filter(|(code, _)| !old.contains_key(*code))
+        .map(|(_, course)| summarize(course, semesters))
+        .collect();
+
+    let removed = old
+        .iter()
+        .filter(|(code, _)| !new.contains_key(*code))
+        .map(|(_, course)| summarize(course, semesters))
+        .collect();
+
+    let changed = new
+        .iter()
+        .filter_map(|(code, after)| {
+            let before = old.get(code)?;
+            let grading = |course: &Course| course.grade_details.clone().unwrap_or_default();
+
+            let course_change = CourseChange {
+                code: after.code.clone(),
+                name: after.name.clone(),
+                credit: change(before.credit, after.credit),
+                semester: change(
+                    semester_titles(before, semesters),
+                    semester_titles(after, semesters),
+                ),
+                nature: change(nature(before), nature(after)),
+                grading: (format_grading(&grading(before)) != format_grading(&grading(after)))
+                    .then(|| Change {
+                        from: grading(before),
+                        to: grading(after),
+                    }),
+            };
+
+            let unchanged = course_change.credit.is_none()
+                && course_change.semester.is_none()
+                && course_change.nature.is_none()
+                && course_change.grading.is_none();
+            (!unchanged).then_some(course_change)
+        })
+        .collect();
+
+    PlanDiff {
+        from_year: from.year.clone(),
+        to_year: to.year.clone(),
+        added,
+        removed,
+        changed,
+    }
+}
+
+/// Table of added or removed courses
+fn summary_table(courses: &[CourseSummary]) -> Vec<String> {
+    let mut lines = vec![
+        "| 课程代码 | 课程名称 | 学分 | 推荐学期 | 课程性质 |".to_string(),
+        "| --- | --- | --- | --- | --- |".to_string(),
+    ];
+    for course in courses {
+        lines.push(format!(
+            "| {} | {} | {} | {} | {} |",
+            course.code,
+            escape_table_cell(&course.name),
+            format_credit(course.credit),
+            escape_table_cell(&course.semester),
+            escape_table_cell(course.nature.as_deref().unwrap_or("—")),
+        ));
+    }
+    lines
+}
+
+/// Build the MDX page comparing two plans of a major
+pub fn build_compare_page(major_name: &str, diff: &PlanDiff) -> String {
+    let summary = if diff.is_empty() {
+        format!(
+            "{} 级与 {} 级{}培养方案相同",
+            diff.from_year, diff.to_year, major_name
+        )
+    } else {
+        format!(
+            "{} 级与 {} 级{}培养方案对比：新增 {} 门，移除 {} 门，调整 {} 门",
+            diff.from_year,
+            diff.to_year,
+            major_name,
+            diff.added.len(),
+            diff.removed.len(),
+            diff.changed.len()
+        )
+    };
+
+    let mut lines = vec![
+        "---".to_string(),
+        format!("title: {} → {} 级", diff.from_year, diff.to_year),
+        format!("description: {}", yaml_string(&summary)),
+        "---".to_string(),
+        "".to_string(),
+        format!("{}。", summary),
+    ];
+
+    if !diff.added.is_empty() {
+        lines.extend(["".to_string(), "## 新增课程".to_string(), "".to_string()]);
+        lines.extend(summary_table(&diff.added));
+    }
+
+    if !diff.removed.is_empty() {
+        lines.extend(["".to_string(), "## 移除课程".to_string(), "".to_string()]);
+        lines.extend(summary_table(&diff.removed));
+    }
+
+    if !diff.changed.is_empty() {
+        lines.extend([
+            "".to_string(),
+            "## 调整课程".to_string(),
+            "".to_string(),
+            format!(
+                "| 课程代码 | 课程名称 | 变更项 | {} 级 | {} 级 |",
+                diff.from_year, diff.to_year
+            ),
+            "| --- | --- | --- | --- | --- |".to_string(),
+        ]);
+
+        for course in &diff.changed {
+            let mut rows: Vec<(&str, String, String)> = Vec::new();
+            if let Some(c) = &course.credit {
+                rows.push(("学分", format_credit(c.from), format_credit(c.to)));
+            }
+            if let Some(c) = &course.semester {
+                rows.push(("推荐学期", c.from.clone(), c.to.clone()));
+            }
+            if let Some(c) = &course.nature {
+                let show = |n: &Option<String>| n.clone().unwrap_or_else(|| "—".to_string());
+                rows.push(("课程性质", show(&c.from), show(&c.to)));
+            }
+            if let Some(c) = &course.grading {
+                rows.push(("考核方案", format_grading(&c.from), format_grading(&c.to)));
+            }
+
+            for (field, from, to) in rows {
+                lines.push(format!(
+                    "| {} | {} | {} | {} | {} |",
+                    course.code,
+                    escape_table_cell(&course.name),
+                    field,
+                    escape_table_cell(&from),
+                    escape_table_cell(&to),
+                ));
+            }
+        }
+    }
+
+    lines.join("\n")
+}
+
+/// Generate comparison pages and JSON artifacts for every major with plans in
+/// more than one enrollment year.
+pub fn generate_comparisons(
+    plans: &[Plan],
+    docs_dir: &Path,
+    public_dir: &Path,
+    semesters: &SemestersConfig,
+) -> Result<()> {
+    // Plans of each major, ordered by year
+    let mut by_major: BTreeMap<&str, Vec<&Plan>> = BTreeMap::new();
+    for plan in plans {
+        by_major
+            .entry(plan.major_code.as_str())
+            .or_default()
+            .push(plan);
+    }
+
+    let compare_dir = docs_dir.join("compare");
+    let data_dir = public_dir.join("data").join("compare");
+    let mut major_cards = Vec::new();
+
+    for (major_code, mut major_plans) in by_major {
+        major_plans.sort_by(|a, b| a.year.cmp(&b.year));
+        if major_plans.len() < 2 {
+            continue;
+        }
+
+        // The most recent plan provides the major name
+        let major_name = major_plans
+            .last()
+            .map(|p| p.major_name.as_str())
+            .unwrap_or(major_code);
+
+        let diffs: Vec<PlanDiff> = major_plans
+            .windows(2)
+            .map(|pair| compare_plans(pair[0], pair[1], semesters))
+            .collect();
+
+        let major_dir = compare_dir.join(major_code);
+        fs::create_dir_all(&major_dir)?;
+
+        // Most recent comparison first
+        let pages: Vec<String> = diffs
+            .iter()
+            .rev()
+            .map(|d| format!("{}-{}", d.from_year, d.to_year))
+            .collect();
+        for (diff, page) in diffs.iter().rev().zip(&pages) {
+            fs::write(
+                major_dir.join(format!("{}.mdx", page)),
+                build_compare_page(major_name, diff),
+            )?;
+        }
+
+        let major_meta = serde_json::json!({
+            "title": major_name,
+            "pages": pages,
+        });
+        fs::write(
+            major_dir.join("meta.json"),
+            serde_json::to_string_pretty(&major_meta)?,
+        )?;
+
+        fs::create_dir_all(&data_dir)?;
+        let artifact = MajorComparisons {
+            major_code,
+            major_name,
+            comparisons: &diffs,
+        };
+        fs::write(
+            data_dir.join(format!("{}.json", major_code)),
+            serde_json::to_string_pretty(&artifact)?,
+        )?;
+
+        major_cards.push(format!(
+            "  <Card title=\"{}\" description=\"{}\" href=\"/docs/compare/{}/{}\" />",
+            major_name, major_code, major_code, pages[0]
+        ));
+    }
+
+    if major_cards.is_empty() {
+        return Ok(());
+    }
+
+    let compare_meta = serde_json::json!({
+        "title": "培养方案对比",
+        "root": true,
+    });
+    fs::write(
+        compare_dir.join("meta.json"),
+        serde_json::to_string_pretty(&compare_meta)?,
+    )?;
+
+    let index = [
+        "---".to_string(),
+        "title: 培养方案对比".to_string(),
+        "description: 同一专业相邻年级培养方案的课程变化".to_string(),
+        "---".to_string(),
+        "".to_string(),
+        "<Cards>".to_string(),
+    ]
+    .into_iter()
+    .chain(major_cards)
+    .chain(["</Cards>".to_string()])
+    .collect::<Vec<_>>()
+    .join("\n");
+    fs::write(compare_dir.join("index.mdx"), index)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support;
+
+    fn course(code: &str, credit: f64, semester: &str) -> Course {
+        test_support::course(code)
+            .with_credit(credit)
+            .with_semester(semester)
+            .with_nature("必修")
+    }
+
+    fn plan(year: &str, courses: Vec<Course>) -> Plan {
+        test_support::plan(year, "080901", courses).with_major_name("计算机")
+    }
+
+    #[test]
+    fn test_compare_plans() {
+        let graded = course("COMP1001", 3.0, "第一学年秋季")
+            .with_grade("平时", "30")
+            .with_grade("期末", "70");
+        let regraded = course("COMP1001", 3.0, "第一学年秋季")
+            .with_grade("平时", "40")
+            .with_grade("期末", "60");
+
+        let renatured = course("COMP2001", 2.0, "第二学年秋季").with_nature("选修");
+
+        let from = plan(
+            "2022",
+            vec![
+                graded,
+                course("COMP2001", 2.0, "第二学年秋季"),
+                course("MATH1001", 5.0, "第一学年秋季"),
+                course("PHYS1001", 4.0, "第一学年春季"),
+                course("SAME1001", 1.0, "第一学年秋季"),
+            ],
+        );
+        let to = plan(
+            "2023",
+            vec![
+                regraded,
+                renatured,
+                course("MATH1001", 5.5, "第一学年春季"),
+                course("NEW1001", 2.0, "第一学年秋季"),
+                course("SAME1001", 1.0, "第一学年秋季学期"),
+            ],
+        );
+
+        let diff = compare_plans(&from, &to, &SemestersConfig::default());
+
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].code, "NEW1001");
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].code, "PHYS1001");
+
+        let codes: Vec<&str> = diff.changed.iter().map(|c| c.code.as_str()).collect();
+        assert_eq!(codes, vec!["COMP1001", "COMP2001", "MATH1001"]);
+
+        let math = &diff.changed[2];
+        assert_eq!(
+            math.credit.as_ref().map(|c| (c.from, c.to)),
+            Some((Some(5.0), Some(5.5)))
+        );
+        assert_eq!(
+            math.semester
+                .as_ref()
+                .map(|c| (c.from.as_str(), c.to.as_str())),
+            Some(("大一·秋", "大一·春"))
+        );
+        assert!(math.nature.is_none() && math.grading.is_none());
+
+        assert!(diff.changed[0].grading.is_some());
+        assert!(diff.changed[1].nature.is_some());
+
+        let json = serde_json::to_value(&diff).unwrap();
+        assert!(json["changed"][2].get("grading").is_none());
+    }
+
+    #[test]
+    fn test_build_compare_page() {
+        let from = plan("2022", vec![course("MATH1001", 5.0, "第一学年秋季")]);
+        let to = plan(
+            "2023",
+            vec![
+                course("MATH1001", 5.5, "第一学年秋季"),
+                course("NEW1001", 2.0, "第一学年秋季"),
+            ],
+        );
+
+        let diff = compare_plans(&from, &to, &SemestersConfig::default());
+        let page = build_compare_page("计算机", &diff);
+
+        assert!(page.starts_with("---\ntitle: 2022 → 2023 级\n"));
+        assert!(page.contains("新增 1 门，移除 0 门，调整 1 门"));
+        assert!(page.contains("## 新增课程"));
+        assert!(!page.contains("## 移除课程"));
+        assert!(page.contains("| NEW1001 | Course NEW1001 | 2 | 大一·秋 | 必修 |"));
+        assert!(page.contains("| MATH1001 | Course MATH1001 | 学分 | 5 | 5.5 |"));
+
+        let same = compare_plans(&from, &from, &SemestersConfig::default());
+        assert!(build_compare_page("计算机", &same).contains("培养方案相同"));
+    }
+
+    #[test]
+    fn test_format_grading() {
+        let details = test_support::course("MATH1001")
+            .with_grade("平时", "30%")
+            .with_grade("期末", "70")
+            .grade_details
+            .unwrap();
+        assert_eq!(format_grading(&details), "平时 30%、期末 70%");
+        assert_eq!(format_grading(&[]), "—");
+    }
+}
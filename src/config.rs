@@ -0,0 +1,249 @@
+//! Optional generator configuration.
+//!
+//! Settings are read from `fuma.toml` in the repository root. Every field has a
+//! default, so the file (and any section in it) may be omitted.
+
+use crate::constants::{parse_semesters, Semester};
+use crate::error::Result;
+use crate::ignore::IgnoreRules;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Name of the configuration file in the repository root
+pub const CONFIG_FILE: &str = "fuma.toml";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub semesters: SemestersConfig,
+    pub semester_index: SemesterIndexConfig,
+    pub file_tree: FileTreeConfig,
+}
+
+/// Settings for placing courses into semester folders
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct SemestersConfig {
+    /// Folder for courses without a recognised recommended semester
+    pub unassigned_folder: String,
+    /// Display title of the unassigned folder
+    pub unassigned_title: String,
+}
+
+impl Default for SemestersConfig {
+    fn default() -> Self {
+        Self {
+            unassigned_folder: "other".to_string(),
+            unassigned_title: "未分学期".to_string(),
+        }
+    }
+}
+
+impl SemestersConfig {
+    /// Folder for courses without a recognised recommended semester, sorted last
+    pub fn unassigned(&self) -> Semester {
+        Semester {
+            order: (usize::MAX, 0),
+            folder: self.unassigned_folder.clone(),
+            title: self.unassigned_title.clone(),
+        }
+    }
+
+    /// Semester folders a course is placed into; never empty
+    pub fn placements(&self, recommended: Option<&str>) -> Vec<Semester> {
+        let semesters = recommended.map(parse_semesters).unwrap_or_default();
+        if semesters.is_empty() {
+            vec![self.unassigned()]
+        } else {
+            semesters
+        }
+    }
+
+    /// Semester folder holding the course page: the first placement
+    pub fn place(&self, recommended: Option<&str>) -> Semester {
+        self.placements(recommended).swap_remove(0)
+    }
+}
+
+/// Settings for the generated semester index pages
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct SemesterIndexConfig {
+    /// Order of course cards within each course nature group
+    pub sort: CourseOrder,
+}
+
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CourseOrder {
+    /// By course code
+    #[default]
+    Code,
+    /// By credit, highest first
+    Credit,
+}
+
+/// Settings for the file trees of course pages
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct FileTreeConfig {
+    /// Also show the files grouped by category below the folder view
+    pub category_view: bool,
+    /// Collapse folders holding more files than this, including subfolders
+    pub collapse_files: Option<usize>,
+    /// Collapse folders nested deeper than this; top-level folders are at depth 1
+    pub collapse_depth: Option<usize>,
+    /// Order of files and folders within each folder
+    pub sort: FileOrder,
+    /// Per-course orders overriding `sort`, by course code
+    pub course_sort: HashMap<String, FileOrder>,
+    /// Gitignore-style rules hiding files of every course
+    pub exclude: Vec<String>,
+}
+
+impl FileTreeConfig {
+    /// Parsed global exclusion rules, after the built-in ones
+    pub fn exclude_rules(&self) -> IgnoreRules {
+        let mut rules = IgnoreRules::builtin();
+        rules.extend(&self.exclude);
+        rules
+    }
+
+    /// Order of the file tree of a course
+    pub fn order(&self, code: &str) -> FileOrder {
+        self.course_sort.get(code).copied().unwrap_or(self.sort)
+    }
+}
+
+/// Order of the entries of a folder; folders always come before files
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum FileOrder {
+    /// By name, with numbers compared by value (`lecture2` before `lecture10`)
+    #[default]
+    Name,
+    /// By modification date, newest first
+    Date,
+    /// By size, largest first
+    Size,
+}
+
+impl Config {
+    /// Load the configuration from the repository root.
+    ///
+    /// Returns the default configuration if the file doesn't exist.
+    pub fn load(repo_root: &Path) -> Result<Self> {
+        let path = repo_root.join(CONFIG_FILE);
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)?;
+        Ok(toml::from_str(&content)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_defaults() {
+        let config: Config = toml::from_str("").unwrap();
+        assert_eq!(config.semester_index.sort, CourseOrder::Code);
+        assert_eq!(config.semesters.unassigned_folder, "other");
+        assert_eq!(config.semesters.unassigned_title, "未分学期");
+        assert!(!config.file_tree.category_view);
+        assert_eq!(config.file_tree.collapse_files, None);
+        assert_eq!(config.file_tree.sort, FileOrder::Name);
+    }
+
+    #[test]
+    fn test_file_tree_order() {
+        let config: Config = toml::from_str(
+            "[file_tree]\nsort = \"date\"\n\n[file_tree.course_sort]\nMATH1001 = \"size\"",
+        )
+        .unwrap();
+        assert_eq!(config.file_tree.order("MATH1001"), FileOrder::Size);
+        assert_eq!(config.file_tree.order("COMP1001"), FileOrder::Date);
+    }
+
+    #[test]
+    fn test_file_tree_exclude() {
+        let config: Config =
+            toml::from_str("[file_tree]\nexclude = [\"*.zip\", \"!keep.zip\"]").unwrap();
+        let rules = config.file_tree.exclude_rules();
+        assert!(rules.is_excluded("lab/data.zip"));
+        assert!(!rules.is_excluded("keep.zip"));
+
+        // Built-in rules come first, so they can be overridden
+        let defaults = Config::default().file_tree.exclude_rules();
+        assert!(defaults.is_excluded("README.md"));
+        assert!(!defaults.is_excluded("a.zip"));
+        let config: Config = toml::from_str("[file_tree]\nexclude = [\"!README.md\"]").unwrap();
+        assert!(!config.file_tree.exclude_rules().is_excluded("README.md"));
+        assert!(config.file_tree.exclude_rules().is_excluded("LICENSE"));
+    }
+
+    #[test]
+    fn test_config_partial_section() {
+        let config: Config = toml::from_str("[semesters]\nunassigned_title = \"其他\"").unwrap();
+        assert_eq!(config.semesters.unassigned_folder, "other");
+        assert_eq!(config.semesters.unassigned_title, "其他");
+    }
+
+    #[test]
+    fn test_semesters_place() {
+        let semesters = SemestersConfig::default();
+        assert_eq!(semesters.place(Some("第一学年秋季")).folder, "fresh-autumn");
+        assert_eq!(semesters.place(Some("unknown")).folder, "other");
+        assert_eq!(semesters.place(None).title, "未分学期");
+        assert!(semesters.place(None).order > semesters.place(Some("夏季学期")).order);
+        assert_eq!(
+            semesters.place(Some("第一学年春季、第一学年秋季")).folder,
+            "fresh-spring"
+        );
+    }
+
+    #[test]
+    fn test_semesters_placements() {
+        let semesters = SemestersConfig::default();
+        let folders: Vec<String> = semesters
+            .placements(Some("第一学年秋季、春季"))
+            .into_iter()
+            .map(|s| s.folder)
+            .collect();
+        assert_eq!(folders, vec!["fresh-autumn", "fresh-spring"]);
+        assert_eq!(
+            semesters.placements(Some("invalid")),
+            vec![semesters.unassigned()]
+        );
+    }
+
+    #[test]
+    fn test_config_semester_index_sort() {
+        let config: Config = toml::from_str("[semester_index]\nsort = \"credit\"").unwrap();
+        assert_eq!(config.semester_index.sort, CourseOrder::Credit);
+    }
+
+    #[test]
+    fn test_config_invalid_value() {
+        let result: std::result::Result<Config, _> =
+            toml::from_str("[semester_index]\nsort = \"name\"");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_load_config_missing_file() {
+        let temp_dir = std::env::temp_dir().join("test_config_missing");
+        let _ = fs::create_dir_all(&temp_dir);
+
+        let config = Config::load(&temp_dir).unwrap();
+        assert_eq!(config.semester_index.sort, CourseOrder::Code);
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+}
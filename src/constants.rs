@@ -18,50 +18,204 @@ pub fn get_semester_folder(recommended: &str) -> Option<(&'static str, &'static
         .map(|&(_, folder, title)| (folder, title))
 }
 
-// ============================================================================
-// File Exclusion Rules
-// ============================================================================
+/// Academic years as written in plan data, with folder prefixes and display names
+const ACADEMIC_YEARS: &[(&str, &str, &str)] = &[
+    ("第一学年", "fresh", "大一"),
+    ("第二学年", "sophomore", "大二"),
+    ("第三学年", "junior", "大三"),
+    ("第四学年", "senior", "大四"),
+    ("第五学年", "fifth", "大五"),
+];
 
-/// Files to exclude from the file tree
-pub const EXCLUDED_PATTERNS: &[&str] = &[".gitkeep", "README.md", "LICENSE", "tag.txt"];
+/// Terms of an academic year in chronological order, with folder suffixes and
+/// display names
+const TERMS: &[(&str, &str, &str)] = &[
+    ("秋季", "autumn", "秋"),
+    ("春季", "spring", "春"),
+    ("夏季", "summer", "夏"),
+];
+
+/// Index of the summer term in [`TERMS`]; ranges only include summer terms at
+/// their ends
+const SUMMER_TERM: usize = 2;
 
-/// File extensions to exclude
-pub const EXCLUDED_EXTENSIONS: &[&str] = &[".toml"];
+/// Separators of semester ranges such as `第二学年秋季-第三学年春季`
+const RANGE_SEPARATORS: &[char] = &['-', '－', '—', '~', '～', '至', '到'];
 
-/// Directory prefixes to exclude
-pub const EXCLUDED_PREFIXES: &[&str] = &[".github/"];
+/// Separators of semester lists such as `第一学年秋季、第一学年春季`
+const LIST_SEPARATORS: &[char] = &['、', ',', '，', ';', '；', '/', '／'];
 
-/// Check if a file path should be included in the file tree
-pub fn should_include_file(path: &str) -> bool {
-    let filename = path.split('/').next_back().unwrap_or("");
+/// A semester folder that courses are placed into
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Semester {
+    /// Chronological sort key: (academic year, term)
+    pub order: (usize, usize),
+    pub folder: String,
+    pub title: String,
+}
 
-    // Check exact matches
-    if EXCLUDED_PATTERNS.contains(&filename) {
-        return false;
+/// Semester of a given academic year and term, as indices into the tables above
+fn semester_at(year: usize, term: usize) -> Semester {
+    let (_, year_folder, year_title) = ACADEMIC_YEARS[year];
+    let (_, term_folder, term_title) = TERMS[term];
+    Semester {
+        order: (year, term),
+        folder: format!("{}-{}", year_folder, term_folder),
+        title: format!("{}·{}", year_title, term_title),
     }
+}
 
-    // Check extensions
-    if EXCLUDED_EXTENSIONS
-        .iter()
-        .any(|ext| filename.ends_with(ext))
-    {
-        return false;
+/// Parse a single recommended semester.
+///
+/// Besides the eight standard semesters this accepts summer terms (`第一学年夏季`),
+/// a fifth academic year, an optional `学期` suffix, a bare academic year
+/// (`第五学年`) or term (`夏季学期`).
+pub fn parse_semester(recommended: &str) -> Option<Semester> {
+    let normalized: String = recommended.chars().filter(|c| !c.is_whitespace()).collect();
+    let start = normalized.strip_suffix("学期").unwrap_or(&normalized);
+
+    if start.is_empty() {
+        return None;
+    }
+
+    // The standard semesters keep their established folders and titles
+    if let Some((folder, title)) = get_semester_folder(start) {
+        let index = SEMESTER_MAPPING
+            .iter()
+            .position(|&(_, f, _)| f == folder)
+            .unwrap_or_default();
+        return Some(Semester {
+            order: (index / 2, index % 2),
+            folder: folder.to_string(),
+            title: title.to_string(),
+        });
     }
 
-    // Check prefixes
-    if EXCLUDED_PREFIXES
+    let year = ACADEMIC_YEARS
         .iter()
-        .any(|prefix| path.starts_with(prefix))
-    {
-        return false;
+        .position(|(key, _, _)| start.starts_with(key));
+    let rest = year.map_or(start, |y| &start[ACADEMIC_YEARS[y].0.len()..]);
+    let term = TERMS.iter().position(|(key, _, _)| rest == *key);
+
+    match (year, term) {
+        (Some(y), Some(t)) => Some(semester_at(y, t)),
+        (Some(y), None) if rest.is_empty() => Some(Semester {
+            order: (y, TERMS.len()),
+            folder: ACADEMIC_YEARS[y].1.to_string(),
+            title: ACADEMIC_YEARS[y].2.to_string(),
+        }),
+        (None, Some(t)) => Some(Semester {
+            order: (ACADEMIC_YEARS.len(), t),
+            folder: TERMS[t].1.to_string(),
+            title: format!("{}学期", TERMS[t].0),
+        }),
+        _ => None,
+    }
+}
+
+/// Parse a recommended semester value that may name several semesters.
+///
+/// Accepts lists (`第一学年秋季、第一学年春季`, where later items may omit the
+/// academic year: `第一学年秋季、春季`) and ranges (`第二学年秋季-第三学年春季`),
+/// which expand to every autumn and spring semester in between. Unrecognised items
+/// are skipped; the result is in order of appearance without duplicates.
+pub fn parse_semesters(recommended: &str) -> Vec<Semester> {
+    let normalized: String = recommended.chars().filter(|c| !c.is_whitespace()).collect();
+    let mut semesters: Vec<Semester> = Vec::new();
+    let mut year_prefix: Option<&str> = None;
+
+    // Parse one item, inheriting the academic year of the previous item
+    let mut parse_item = |item: &str| {
+        let item = match year_prefix {
+            Some(year) if TERMS.iter().any(|(key, _, _)| item.starts_with(key)) => {
+                format!("{}{}", year, item)
+            }
+            _ => item.to_string(),
+        };
+        if let Some((key, _, _)) = ACADEMIC_YEARS
+            .iter()
+            .find(|(key, _, _)| item.starts_with(key))
+        {
+            year_prefix = Some(key);
+        }
+        parse_semester(&item)
+    };
+
+    for item in normalized.split(LIST_SEPARATORS).filter(|i| !i.is_empty()) {
+        let mut bounds = item.splitn(2, RANGE_SEPARATORS);
+        let start = bounds.next().and_then(&mut parse_item);
+        let end = bounds.next().and_then(&mut parse_item);
+
+        let expanded = match (start, end) {
+            (Some(start), Some(end)) => expand_range(start, end),
+            (Some(start), None) => vec![start],
+            (None, Some(end)) => vec![end],
+            (None, None) => vec![],
+        };
+        for semester in expanded {
+            if !semesters.contains(&semester) {
+                semesters.push(semester);
+            }
+        }
     }
 
-    true
+    semesters
 }
 
+/// Every semester from `start` to `end`; summer terms are only kept at the ends
+fn expand_range(start: Semester, end: Semester) -> Vec<Semester> {
+    let is_term = |s: &Semester| s.order.0 < ACADEMIC_YEARS.len() && s.order.1 < TERMS.len();
+    if !is_term(&start) || !is_term(&end) || start.order >= end.order {
+        return vec![start, end];
+    }
+
+    (start.order.0..=end.order.0)
+        .flat_map(|year| (0..TERMS.len()).map(move |term| (year, term)))
+        .filter(|&order| order >= start.order && order <= end.order)
+        .filter(|&order| order == start.order || order == end.order || order.1 != SUMMER_TERM)
+        .map(|(year, term)| {
+            let semester = semester_at(year, term);
+            // Keep the standard titles and folders of the endpoints
+            if semester.order == start.order {
+                start.clone()
+            } else if semester.order == end.order {
+                end.clone()
+            } else {
+                semester
+            }
+        })
+        .collect()
+}
+
+/// Usual order of course natures in index pages; other natures follow alphabetically
+pub const COURSE_NATURE_ORDER: &[&str] = &["必修", "限选", "选修", "任选"];
+
+/// Group title for courses without a course nature
+pub const UNKNOWN_COURSE_NATURE: &str = "其他";
+
+// ============================================================================
+// File Exclusion Rules
+// ============================================================================
+
+/// Files excluded from the file tree before any configured rule, in `.gitignore`
+/// syntax (see [`crate::ignore`]), so configured rules can re-include them
+pub const BUILTIN_EXCLUDE_RULES: &[&str] = &[
+    ".gitkeep",
+    "README.md",
+    "LICENSE",
+    "tag.txt",
+    "*.toml",
+    "/.github/",
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ignore::IgnoreRules;
+
+    fn should_include_file(path: &str) -> bool {
+        !IgnoreRules::builtin().is_excluded(path)
+    }
 
     #[test]
     fn test_get_semester_folder_valid() {
@@ -87,6 +241,105 @@ mod tests {
         assert_eq!(result, None);
     }
 
+    #[test]
+    fn test_parse_semester_standard_matches_mapping() {
+        for (key, folder, title) in SEMESTER_MAPPING {
+            let semester = parse_semester(key).unwrap();
+            assert_eq!(semester.folder, *folder);
+            assert_eq!(semester.title, *title);
+        }
+    }
+
+    #[test]
+    fn test_parse_semester_non_standard() {
+        let semester = parse_semester("第一学年夏季学期").unwrap();
+        assert_eq!(semester.folder, "fresh-summer");
+        assert_eq!(semester.title, "大一·夏");
+
+        let semester = parse_semester("第五学年秋季").unwrap();
+        assert_eq!(semester.folder, "fifth-autumn");
+        assert_eq!(semester.title, "大五·秋");
+
+        let semester = parse_semester("第五学年").unwrap();
+        assert_eq!(semester.folder, "fifth");
+        assert_eq!(semester.title, "大五");
+
+        let semester = parse_semester("夏季学期").unwrap();
+        assert_eq!(semester.folder, "summer");
+        assert_eq!(semester.title, "夏季学期");
+
+        assert_eq!(parse_semester("第二学年秋季-第三学年春季"), None);
+    }
+
+    #[test]
+    fn test_parse_semesters_list() {
+        let folders =
+            |s: &str| -> Vec<String> { parse_semesters(s).into_iter().map(|s| s.folder).collect() };
+
+        assert_eq!(folders("第一学年秋季"), vec!["fresh-autumn"]);
+        assert_eq!(
+            folders("第一学年秋季、第一学年春季"),
+            vec!["fresh-autumn", "fresh-spring"]
+        );
+        assert_eq!(
+            folders("第一学年秋季, 春季；第二学年秋季/春季"),
+            vec![
+                "fresh-autumn",
+                "fresh-spring",
+                "sophomore-autumn",
+                "sophomore-spring"
+            ]
+        );
+        assert_eq!(
+            folders("第一学年秋季、invalid、第一学年秋季"),
+            vec!["fresh-autumn"]
+        );
+        assert!(folders("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_semesters_range() {
+        let folders =
+            |s: &str| -> Vec<String> { parse_semesters(s).into_iter().map(|s| s.folder).collect() };
+
+        assert_eq!(
+            folders(" 第二学年秋季 - 第三学年春季 "),
+            vec![
+                "sophomore-autumn",
+                "sophomore-spring",
+                "junior-autumn",
+                "junior-spring"
+            ]
+        );
+        assert_eq!(
+            folders("第一学年秋季至第一学年夏季"),
+            vec!["fresh-autumn", "fresh-spring", "fresh-summer"]
+        );
+        assert_eq!(
+            folders("第一学年秋季~春季"),
+            vec!["fresh-autumn", "fresh-spring"]
+        );
+        assert_eq!(folders("第一学年-第二学年"), vec!["fresh", "sophomore"]);
+    }
+
+    #[test]
+    fn test_parse_semester_order() {
+        let order = |s: &str| parse_semester(s).unwrap().order;
+        assert!(order("第一学年秋季") < order("第一学年春季"));
+        assert!(order("第一学年春季") < order("第一学年夏季"));
+        assert!(order("第一学年夏季") < order("第一学年"));
+        assert!(order("第一学年") < order("第二学年秋季"));
+        assert!(order("第五学年春季") < order("夏季学期"));
+    }
+
+    #[test]
+    fn test_parse_semester_invalid() {
+        assert_eq!(parse_semester(""), None);
+        assert_eq!(parse_semester("invalid"), None);
+        assert_eq!(parse_semester("第六学年秋季"), None);
+        assert_eq!(parse_semester("第一学年冬季"), None);
+    }
+
     #[test]
     fn test_should_include_file_excluded_patterns() {
         assert!(!should_include_file(".gitkeep"));
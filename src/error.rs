@@ -12,6 +12,18 @@ pub enum FumaError {
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
+    #[error("Watch error: {0}")]
+    Watch(#[from] notify::Error),
+
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+
     #[error("Missing required directory: {0}")]
     MissingDirectory(PathBuf),
 }
@@ -0,0 +1,480 @@
+//! Export of the merged plan dataset for other tools.
+//!
+//! The `export` command writes the plans as loaded by [`loader::load_all_plans`]
+//! (plan TOMLs merged with `grades_summary.json`) without generating any pages:
+//!
+//! - `plans.json`: a versioned document holding every plan with its courses
+//! - `courses.ndjson`: one JSON object per line for every course of every plan
+//!
+//! With `--sqlite`, the same data is written as normalized tables into a SQLite
+//! database instead, together with the resource files from worktree data.
+//!
+//! The schema is documented in the README; incompatible changes bump
+//! [`SCHEMA_VERSION`].
+
+use crate::config::Config;
+use crate::error::{FumaError, Result};
+use crate::loader;
+use crate::models::{latest_course_names, Course, Plan};
+use crate::tree::FileTrees;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Version of the export schema
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Default output directory, relative to the repository root
+const DEFAULT_OUTPUT_DIR: &str = "export";
+
+/// File name of the SQLite database in the output directory
+const SQLITE_FILE: &str = "hoa.sqlite";
+
+/// Schema of the SQLite export
+const SQLITE_SCHEMA: &str = "
+CREATE TABLE meta (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+CREATE TABLE plans (
+    id INTEGER PRIMARY KEY,
+    year TEXT NOT NULL,
+    major_code TEXT NOT NULL,
+    major_name TEXT NOT NULL,
+    UNIQUE (year, major_code)
+);
+CREATE TABLE courses (
+    code TEXT PRIMARY KEY,
+    name TEXT NOT NULL
+);
+CREATE TABLE plan_courses (
+    plan_id INTEGER NOT NULL REFERENCES plans (id),
+    course_code TEXT NOT NULL REFERENCES courses (code),
+    credit REAL,
+    assessment_method TEXT,
+    course_nature TEXT,
+    recommended_semester TEXT,
+    PRIMARY KEY (plan_id, course_code)
+);
+CREATE TABLE grading_items (
+    plan_id INTEGER NOT NULL REFERENCES plans (id),
+    course_code TEXT NOT NULL REFERENCES courses (code),
+    position INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    percent TEXT,
+    PRIMARY KEY (plan_id, course_code, position)
+);
+CREATE TABLE hour_distributions (
+    plan_id INTEGER NOT NULL REFERENCES plans (id),
+    course_code TEXT NOT NULL REFERENCES courses (code),
+    theory INTEGER,
+    lab INTEGER,
+    practice INTEGER,
+    exercise INTEGER,
+    computer INTEGER,
+    tutoring INTEGER,
+    PRIMARY KEY (plan_id, course_code)
+);
+CREATE TABLE resource_files (
+    course_code TEXT NOT NULL REFERENCES courses (code),
+    path TEXT NOT NULL,
+    size INTEGER,
+    modified_at INTEGER,
+    PRIMARY KEY (course_code, path)
+);
+CREATE INDEX plan_courses_course ON plan_courses (course_code);
+";
+
+/// Top-level document of `plans.json`
+#[derive(Debug, Serialize)]
+struct PlansDocument<'a> {
+    schema_version: u32,
+    generated_at: String,
+    plans: &'a [Plan],
+}
+
+/// A line of `courses.ndjson`: a course together with the plan including it
+#[derive(Debug, Serialize)]
+struct CourseRow<'a> {
+    schema_version: u32,
+    year: &'a str,
+    major_code: &'a str,
+    major_name: &'a str,
+    #[serde(flatten)]
+    course: &'a Course,
+}
+
+/// Options of the `export` command
+#[derive(Debug, PartialEq)]
+pub struct ExportOptions {
+    pub output_dir: PathBuf,
+    /// Write a SQLite database instead of JSON files
+    pub sqlite: bool,
+}
+
+impl ExportOptions {
+    /// Parse the arguments following `export`
+    pub fn parse(repo_root: &Path, args: &[String]) -> Result<Self> {
+        let mut output_dir = repo_root.join(DEFAULT_OUTPUT_DIR);
+        let mut sqlite = false;
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--out" => {
+                    let dir = args.next().ok_or_else(|| {
+                        FumaError::InvalidArgument("--out requires a directory".to_string())
+                    })?;
+                    output_dir = PathBuf::from(dir);
+                }
+                "--sqlite" => sqlite = true,
+                other => {
+                    return Err(FumaError::InvalidArgument(format!(
+                        "unknown export option: {}",
+                        other
+                    )));
+                }
+            }
+        }
+
+        Ok(Self { output_dir, sqlite })
+    }
+}
+
+/// Plans in a stable order: by year, then major code
+fn sorted_plans(mut plans: Vec<Plan>) -> Vec<Plan> {
+    plans.sort_by(|a, b| {
+        a.year
+            .cmp(&b.year)
+            .then_with(|| a.major_code.cmp(&b.major_code))
+    });
+    plans
+}
+
+/// Write `plans.json`
+pub fn write_plans_json(plans: &[Plan], path: &Path) -> Result<()> {
+    let document = PlansDocument {
+        schema_version: SCHEMA_VERSION,
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        plans,
+    };
+    fs::write(path, serde_json::to_string_pretty(&document)?)?;
+    Ok(())
+}
+
+/// Write `courses.ndjson`, one course of one plan per line
+pub fn write_courses_ndjson(plans: &[Plan], path: &Path) -> Result<()> {
+    let mut writer = BufWriter::new(fs::File::create(path)?);
+
+    for plan in plans {
+        for course in &plan.courses {
+            let row = CourseRow {
+                schema_version: SCHEMA_VERSION,
+                year: &plan.year,
+                major_code: &plan.major_code,
+                major_name: &plan.major_name,
+                course,
+            };
+            serde_json::to_writer(&mut writer, &row)?;
+            writeln!(writer)?;
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Write the plans and the resource files of their courses into a new SQLite
+/// database, replacing any existing file.
+///
+/// Courses are named after their most recent plan. Resource files come from the
+/// worktree data in `trees`, with the same exclusions as the file trees.
+pub fn write_sqlite(plans: &[Plan], trees: &FileTrees, path: &Path) -> Result<()> {
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+
+    let mut conn = Connection::open(path)?;
+    conn.execute_batch(SQLITE_SCHEMA)?;
+    let tx = conn.transaction()?;
+
+    tx.execute(
+        "INSERT INTO meta (key, value) VALUES ('schema_version', ?1)",
+        params![SCHEMA_VERSION.to_string()],
+    )?;
+
+    let names = latest_course_names(plans);
+    for (code, name) in &names {
+        tx.execute(
+            "INSERT INTO courses (code, name) VALUES (?1, ?2)",
+            params![code, name],
+        )?;
+    }
+
+    for plan in plans {
+        tx.execute(
+            "INSERT OR IGNORE INTO plans (year, major_code, major_name) VALUES (?1, ?2, ?3)",
+            params![plan.year, plan.major_code, plan.major_name],
+        )?;
+        let plan_id: i64 = tx.query_row(
+            "SELECT id FROM plans WHERE year = ?1 AND major_code = ?2",
+            params![plan.year, plan.major_code],
+            |row| row.get(0),
+        )?;
+
+        // Only the first occurrence of a course within a plan is kept
+        let mut seen = HashSet::new();
+        for course in plan.courses.iter().filter(|c| seen.insert(&c.code)) {
+            let inserted = tx.execute(
+                "INSERT OR IGNORE INTO plan_courses
+                 (plan_id, course_code, credit, assessment_method, course_nature, recommended_semester)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    plan_id,
+                    course.code,
+                    course.credit,
+                    course.assessment_method,
+                    course.course_nature,
+                    course.recommended_semester
+                ],
+            )?;
+            if inserted == 0 {
+                continue;
+            }
+
+            for (position, item) in course.grade_details.iter().flatten().enumerate() {
+                tx.execute(
+                    "INSERT INTO grading_items (plan_id, course_code, position, name, percent)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![
+                        plan_id,
+                        course.code,
+                        position as i64,
+                        item.name,
+                        item.percent
+                    ],
+                )?;
+            }
+
+            if let Some(hours) = &course.hours {
+                tx.execute(
+                    "INSERT INTO hour_distributions
+                     (plan_id, course_code, theory, lab, practice, exercise, computer, tutoring)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    params![
+                        plan_id,
+                        course.code,
+                        hours.theory,
+                        hours.lab,
+                        hours.practice,
+                        hours.exercise,
+                        hours.computer,
+                        hours.tutoring
+                    ],
+                )?;
+            }
+        }
+    }
+
+    for code in names.keys() {
+        let Some(tree) = trees.get(*code) else {
+            continue;
+        };
+        for (file_path, meta) in &tree.worktree.0 {
+            tx.execute(
+                "INSERT INTO resource_files (course_code, path, size, modified_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![code, file_path, meta.size.map(|s| s as i64), meta.time],
+            )?;
+        }
+    }
+
+    tx.commit()?;
+    Ok(())
+}
+
+/// Run the `export` command
+pub fn run(repo_root: &Path, args: &[String]) -> Result<()> {
+    let options = ExportOptions::parse(repo_root, args)?;
+
+    let data_dir = repo_root.join("hoa-major-data");
+    let plans = sorted_plans(loader::load_all_plans(&data_dir)?);
+    println!("Loaded {} training plans", plans.len());
+
+    fs::create_dir_all(&options.output_dir)?;
+
+    if options.sqlite {
+        let db_path = options.output_dir.join(SQLITE_FILE);
+        let exclude = Config::load(repo_root)?.file_tree.exclude_rules();
+        let trees = loader::load_file_trees(&plans, &repo_root.join("repos"), &exclude)?;
+        write_sqlite(&plans, &trees, &db_path)?;
+        println!("Wrote {}", db_path.display());
+        return Ok(());
+    }
+
+    let plans_path = options.output_dir.join("plans.json");
+    write_plans_json(&plans, &plans_path)?;
+    println!("Wrote {}", plans_path.display());
+
+    let courses_path = options.output_dir.join("courses.ndjson");
+    write_courses_ndjson(&plans, &courses_path)?;
+    println!("Wrote {}", courses_path.display());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ignore::IgnoreRules;
+    use crate::models::test_support::course;
+
+    fn plan(year: &str, major_code: &str) -> Plan {
+        let math = course("MATH1001")
+            .with_name("高等数学")
+            .with_credit(5.5)
+            .with_assessment("考试")
+            .with_nature("必修")
+            .with_semester("第一学年秋季")
+            .with_grade("期末", "70");
+        crate::models::test_support::plan(year, major_code, vec![math]).with_major_name("计算机")
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let root = Path::new("/repo");
+        let defaults = ExportOptions::parse(root, &[]).unwrap();
+        assert_eq!(defaults.output_dir, root.join("export"));
+        assert!(!defaults.sqlite);
+        assert!(
+            ExportOptions::parse(root, &["--sqlite".to_string()])
+                .unwrap()
+                .sqlite
+        );
+
+        let args = vec!["--out".to_string(), "/tmp/out".to_string()];
+        assert_eq!(
+            ExportOptions::parse(root, &args).unwrap().output_dir,
+            PathBuf::from("/tmp/out")
+        );
+
+        assert!(matches!(
+            ExportOptions::parse(root, &["--out".to_string()]),
+            Err(FumaError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            ExportOptions::parse(root, &["--bogus".to_string()]),
+            Err(FumaError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_sorted_plans() {
+        let plans = sorted_plans(vec![
+            plan("2023", "2"),
+            plan("2022", "9"),
+            plan("2023", "1"),
+        ]);
+        let keys: Vec<(&str, &str)> = plans
+            .iter()
+            .map(|p| (p.year.as_str(), p.major_code.as_str()))
+            .collect();
+        assert_eq!(keys, vec![("2022", "9"), ("2023", "1"), ("2023", "2")]);
+    }
+
+    #[test]
+    fn test_write_exports() {
+        let temp_dir = std::env::temp_dir().join("test_write_exports");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+
+        let plans = vec![plan("2022", "080901"), plan("2023", "080901")];
+
+        write_plans_json(&plans, &temp_dir.join("plans.json")).unwrap();
+        let document: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(temp_dir.join("plans.json")).unwrap())
+                .unwrap();
+        assert_eq!(document["schema_version"], SCHEMA_VERSION);
+        assert_eq!(document["plans"][1]["year"], "2023");
+        assert_eq!(document["plans"][0]["courses"][0]["credit"], 5.5);
+        assert_eq!(
+            document["plans"][0]["courses"][0]["grade_details"][0]["name"],
+            "期末"
+        );
+
+        write_courses_ndjson(&plans, &temp_dir.join("courses.ndjson")).unwrap();
+        let content = fs::read_to_string(temp_dir.join("courses.ndjson")).unwrap();
+        let rows: Vec<serde_json::Value> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["year"], "2022");
+        assert_eq!(rows[0]["major_code"], "080901");
+        assert_eq!(rows[0]["code"], "MATH1001");
+        assert_eq!(rows[0]["recommended_semester"], "第一学年秋季");
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_write_sqlite() {
+        let temp_dir = std::env::temp_dir().join("test_write_sqlite");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        fs::write(
+            temp_dir.join("MATH1001.json"),
+            r#"{"a.pdf": {"size": 10, "time": 1640000000}, "README.md": {"size": 1, "time": null}}"#,
+        )
+        .unwrap();
+
+        let mut p2023 = plan("2023", "080901");
+        p2023.courses[0].name = "高等数学A".to_string();
+        p2023.courses.push(p2023.courses[0].clone());
+        let plans = vec![plan("2022", "080901"), p2023, plan("2022", "080902")];
+
+        let trees = loader::load_file_trees(&plans, &temp_dir, &IgnoreRules::builtin()).unwrap();
+        let db_path = temp_dir.join("hoa.sqlite");
+        write_sqlite(&plans, &trees, &db_path).unwrap();
+        // Re-exporting replaces the database
+        write_sqlite(&plans, &trees, &db_path).unwrap();
+
+        let conn = Connection::open(&db_path).unwrap();
+        let count = |table: &str| -> i64 {
+            conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+                row.get(0)
+            })
+            .unwrap()
+        };
+        assert_eq!(count("plans"), 3);
+        assert_eq!(count("courses"), 1);
+        assert_eq!(count("plan_courses"), 3);
+        assert_eq!(count("grading_items"), 3);
+        assert_eq!(count("hour_distributions"), 0);
+        assert_eq!(count("resource_files"), 1);
+
+        let name: String = conn
+            .query_row(
+                "SELECT name FROM courses WHERE code = 'MATH1001'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(name, "高等数学A");
+
+        let shared: i64 = conn
+            .query_row(
+                "SELECT COUNT(DISTINCT p.major_code) FROM plan_courses pc
+                 JOIN plans p ON p.id = pc.plan_id WHERE pc.course_code = 'MATH1001'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(shared, 2);
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+}
@@ -1,9 +1,11 @@
 //! GitHub repository data fetcher module.
 //!
-//! This module provides functionality to fetch README.md and worktree.json files
-//! from GitHub repositories, replacing the Python-based fetching logic.
+//! This module provides functionality to fetch README.md, worktree.json and the
+//! optional `.hoaignore` files from GitHub repositories, replacing the
+//! Python-based fetching logic.
 
 use crate::error::{FumaError, Result};
+use crate::ignore::IGNORE_EXTENSION;
 use base64::prelude::*;
 use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, USER_AGENT};
 use serde::Deserialize;
@@ -72,10 +74,18 @@ impl GitHubFetcher {
             .await
             .map_err(|e| FumaError::Io(std::io::Error::other(e)))?;
 
-        if !response.status().is_success() {
+        // Only a 404 means the file doesn't exist; rate limits and server errors
+        // may go away on the next fetch
+        let status = response.status();
+        if !status.is_success() {
+            let kind = if status == reqwest::StatusCode::NOT_FOUND {
+                std::io::ErrorKind::NotFound
+            } else {
+                std::io::ErrorKind::Other
+            };
             return Err(FumaError::Io(std::io::Error::new(
-                std::io::ErrorKind::NotFound,
-                format!("GitHub API returned status: {}", response.status()),
+                kind,
+                format!("GitHub API returned status: {}", status),
             )));
         }
 
@@ -110,6 +120,12 @@ impl GitHubFetcher {
             .await
     }
 
+    /// Fetch the `.hoaignore` exclusion rules of a repository
+    pub async fn fetch_ignore_rules(&self, org: &str, repo: &str) -> Result<String> {
+        self.fetch_file(org, repo, &format!(".{}", IGNORE_EXTENSION), None)
+            .await
+    }
+
     /// Fetch repository data and save to local files
     pub async fn fetch_repo_data(&self, org: &str, repo: &str, repos_dir: &Path) -> Result<()> {
         let mdx_path = repos_dir.join(format!("{}.mdx", repo));
@@ -137,6 +153,25 @@ impl GitHubFetcher {
                     eprintln!("Warning: Failed to fetch worktree.json for {}: {}", repo, e);
                 }
             }
+
+            // Refreshed with the worktree. Most repositories don't have one, so
+            // rules left from an earlier fetch are only dropped once the file is
+            // confirmed gone; on other errors they keep hiding their files
+            let ignore_path = repos_dir.join(format!("{}.{}", repo, IGNORE_EXTENSION));
+            match self.fetch_ignore_rules(org, repo).await {
+                Ok(content) => fs::write(&ignore_path, content).await?,
+                Err(FumaError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+                    if ignore_path.exists() {
+                        fs::remove_file(&ignore_path).await?;
+                    }
+                }
+                Err(e) => {
+                    eprintln!(
+                        "Warning: Failed to fetch .{} for {}, keeping the existing rules: {}",
+                        IGNORE_EXTENSION, repo, e
+                    );
+                }
+            }
         }
 
         Ok(())
@@ -210,6 +245,24 @@ pub async fn fetch_all_repos(
     Ok(())
 }
 
+/// Build a plain HTTP client for downloading public assets.
+///
+/// Unlike [`GitHubFetcher`], this client carries no credentials, so it is safe to
+/// point at arbitrary hosts referenced from course READMEs.
+pub fn asset_client() -> Result<reqwest::Client> {
+    Ok(reqwest::Client::builder()
+        .user_agent("fuma-rs")
+        .timeout(std::time::Duration::from_secs(60))
+        .build()?)
+}
+
+/// Download a public asset as raw bytes
+pub async fn fetch_asset(client: &reqwest::Client, url: &str) -> Result<Vec<u8>> {
+    // Error statuses become errors carrying the URL and status
+    let response = client.get(url).send().await?.error_for_status()?;
+    Ok(response.bytes().await?.to_vec())
+}
+
 /// Resolve GitHub token from environment variables
 pub fn resolve_github_token() -> Option<String> {
     // Priority order:
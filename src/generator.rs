@@ -1,19 +1,24 @@
-use crate::constants::{get_semester_folder, SEMESTER_MAPPING};
+use crate::assets::{discover_images, AssetMirror, MIRROR_CONCURRENCY};
+use crate::config::{Config, CourseOrder, SemestersConfig};
+use crate::constants::{parse_semesters, Semester, COURSE_NATURE_ORDER, UNKNOWN_COURSE_NATURE};
 use crate::error::Result;
 use crate::models::{
-    Course, CourseMetadata, Frontmatter, GradingItem, HourDistributionMeta, Plan, WorktreeData,
+    Course, CourseMetadata, CoursePageFrontmatter, Frontmatter, GradingItem, HourDistribution,
+    HourDistributionMeta, Plan,
 };
-use crate::tree::{build_file_tree, tree_to_jsx};
-use std::collections::{HashMap, HashSet};
+use crate::pinyin;
+use crate::readme::{extract_description, rewrite_links, split_readme, ReadmeParts};
+use crate::tree::{category_view_jsx, sort_tree, summarize_files, tree_to_jsx, FileTrees};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 // ============================================================================
 // Frontmatter Generation
 // ============================================================================
 
-/// Build YAML frontmatter for a course page using serde_yaml
-fn build_frontmatter(title: &str, course: &Course) -> String {
+/// Build YAML frontmatter for a plan-specific course page using serde_yaml
+fn build_frontmatter(title: &str, description: &str, course: &Course) -> String {
     let credit = course.credit.map(|c| c as u32).unwrap_or(0);
     let assessment_method = course
         .assessment_method
@@ -67,7 +72,8 @@ fn build_frontmatter(title: &str, course: &Course) -> String {
 
     let frontmatter = Frontmatter {
         title: title.to_string(),
-        description: String::new(),
+        description: description.to_string(),
+        keywords: pinyin::keywords(title),
         course: CourseMetadata {
             credit,
             assessment_method,
@@ -84,192 +90,1626 @@ fn build_frontmatter(title: &str, course: &Course) -> String {
 // Page Generation
 // ============================================================================
 
+/// Frontmatter line with the pinyin keywords of a name, if it has any
+pub fn keywords_line(name: &str) -> Option<String> {
+    let keywords = pinyin::keywords(name);
+    (!keywords.is_empty()).then(|| format!("keywords: [{}]", keywords.join(", ")))
+}
+
+/// Quote a frontmatter value; a JSON string is valid YAML whatever the text holds
+pub fn yaml_string(value: &str) -> String {
+    serde_json::Value::from(value).to_string()
+}
+
+/// Site path of the canonical page of a course
+pub fn course_page_href(code: &str) -> String {
+    format!("/docs/courses/{}", code)
+}
+
+/// Site path of the page generated for a course within a plan
+fn plan_course_href(plan: &Plan, course: &Course, semesters: &SemestersConfig) -> String {
+    format!(
+        "/docs/{}/{}/{}/{}",
+        plan.year,
+        plan.major_code,
+        semesters
+            .place(course.recommended_semester.as_deref())
+            .folder,
+        course.code
+    )
+}
+
+/// Format a credit value without a trailing `.0`
+pub fn format_credit(credit: Option<f64>) -> String {
+    match credit {
+        Some(c) if c.fract() == 0.0 => format!("{}", c as i64),
+        Some(c) => format!("{}", c),
+        None => "—".to_string(),
+    }
+}
+
+/// Format the range of credits a course has across plans, e.g. `5–5.5`
+pub fn format_credit_range<I: IntoIterator<Item = Option<f64>>>(credits: I) -> String {
+    let credits: Vec<f64> = credits.into_iter().flatten().collect();
+    let min = credits.iter().copied().reduce(f64::min);
+    let max = credits.iter().copied().reduce(f64::max);
+
+    match (min, max) {
+        (Some(min), Some(max)) if min != max => {
+            format!("{}–{}", format_credit(Some(min)), format_credit(Some(max)))
+        }
+        _ => format_credit(min),
+    }
+}
+
+/// Escape text for use inside a Markdown table cell
+pub fn escape_table_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+/// Build the "which plans include this course" table of a canonical page
+fn build_plans_table(occurrences: &[(&Plan, &Course)], semesters: &SemestersConfig) -> String {
+    let mut occurrences = occurrences.to_vec();
+    occurrences.sort_by(|(a, _), (b, _)| a.cmp_recency(b));
+
+    let mut lines = vec![
+        "## 开设情况".to_string(),
+        "".to_string(),
+        "| 年级 | 专业 | 推荐学期 | 学分 | 课程性质 |".to_string(),
+        "| --- | --- | --- | --- | --- |".to_string(),
+    ];
+
+    for (plan, course) in occurrences {
+        let semester = match course.recommended_semester.as_deref() {
+            Some(sem) => {
+                let titles: Vec<String> =
+                    parse_semesters(sem).into_iter().map(|s| s.title).collect();
+                if titles.is_empty() {
+                    sem.to_string()
+                } else {
+                    titles.join("、")
+                }
+            }
+            None => "—".to_string(),
+        };
+        lines.push(format!(
+            "| {} | [{}]({}) | {} | {} | {} |",
+            plan.year,
+            escape_table_cell(&plan.major_name),
+            plan_course_href(plan, course, semesters),
+            escape_table_cell(&semester),
+            format_credit(course.credit),
+            escape_table_cell(course.course_nature.as_deref().unwrap_or("—")),
+        ));
+    }
+
+    lines.join("\n")
+}
+
+/// Group courses by course nature, known natures first in their usual order
+fn group_by_nature<'a>(courses: &[&'a Course]) -> Vec<(&'a str, Vec<&'a Course>)> {
+    let mut groups: Vec<(&str, Vec<&Course>)> = Vec::new();
+    for course in courses {
+        let nature = course
+            .course_nature
+            .as_deref()
+            .filter(|n| !n.trim().is_empty())
+            .unwrap_or(UNKNOWN_COURSE_NATURE);
+        match groups.iter_mut().find(|(n, _)| *n == nature) {
+            Some((_, group)) => group.push(course),
+            None => groups.push((nature, vec![course])),
+        }
+    }
+    groups.sort_by_key(|(nature, _)| {
+        let rank = COURSE_NATURE_ORDER
+            .iter()
+            .position(|n| n == nature)
+            .unwrap_or(if *nature == UNKNOWN_COURSE_NATURE {
+                usize::MAX
+            } else {
+                COURSE_NATURE_ORDER.len()
+            });
+        (rank, nature.to_string())
+    });
+    groups
+}
+
+/// Sum of course credits
+fn total_credit(courses: &[&Course]) -> f64 {
+    courses.iter().filter_map(|c| c.credit).sum()
+}
+
+/// Build a semester index page.
+///
+/// Course cards are grouped by course nature and sorted within each group; each
+/// card shows the credit and assessment method, and the page states the total
+/// credits of the semester.
+fn build_semester_index<F: Fn(&Course) -> String>(
+    title: &str,
+    courses: &[&Course],
+    order: CourseOrder,
+    course_href: F,
+) -> String {
+    let summary = format!(
+        "本学期共 {} 门课程，合计 {} 学分",
+        courses.len(),
+        format_credit(Some(total_credit(courses)))
+    );
+
+    let mut lines = vec![
+        "---".to_string(),
+        format!("title: {}", yaml_string(title)),
+        format!("description: {}", yaml_string(&summary)),
+        "---".to_string(),
+        "".to_string(),
+        format!("{}。", summary),
+    ];
+
+    for (nature, mut group) in group_by_nature(courses) {
+        match order {
+            CourseOrder::Code => group.sort_by(|a, b| a.code.cmp(&b.code)),
+            CourseOrder::Credit => group.sort_by(|a, b| {
+                b.credit
+                    .unwrap_or(0.0)
+                    .total_cmp(&a.credit.unwrap_or(0.0))
+                    .then_with(|| a.code.cmp(&b.code))
+            }),
+        }
+
+        lines.push("".to_string());
+        lines.push(format!(
+            "## {}（{} 学分）",
+            nature,
+            format_credit(Some(total_credit(&group)))
+        ));
+        lines.push("".to_string());
+        lines.push("<Cards>".to_string());
+
+        for course in group {
+            let mut details = Vec::new();
+            if course.credit.is_some() {
+                details.push(format!("{} 学分", format_credit(course.credit)));
+            }
+            if let Some(method) = course.assessment_method.as_deref() {
+                if !method.trim().is_empty() {
+                    details.push(method.to_string());
+                }
+            }
+            let description = if details.is_empty() {
+                String::new()
+            } else {
+                format!(" description=\"{}\"", details.join(" · "))
+            };
+            lines.push(format!(
+                "  <Card title=\"{}\"{} href=\"{}\" />",
+                course.name,
+                description,
+                course_href(course)
+            ));
+        }
+        lines.push("</Cards>".to_string());
+    }
+
+    lines.join("\n")
+}
+
+/// Build the overview page of a plan.
+///
+/// Semester cards only cover semesters with generated course pages; the
+/// statistics and the course table cover every course of the plan. Courses
+/// spanning several semesters count towards their first semester.
+fn build_plan_overview<F, G>(
+    plan: &Plan,
+    semesters: &SemestersConfig,
+    has_page: F,
+    file_count: G,
+) -> String
+where
+    F: Fn(&Course) -> bool,
+    G: Fn(&Course) -> usize,
+{
+    let courses: Vec<&Course> = plan.courses.iter().collect();
+    let with_page: Vec<&Course> = courses.iter().copied().filter(|c| has_page(c)).collect();
+    let with_files = courses.iter().filter(|c| file_count(c) > 0).count();
+    let summary = format!(
+        "{} 级{}培养方案，共 {} 门课程，合计 {} 学分",
+        plan.year,
+        plan.major_name,
+        courses.len(),
+        format_credit(Some(total_credit(&courses)))
+    );
+
+    let mut lines = vec![
+        "---".to_string(),
+        "title: 培养方案概览".to_string(),
+        format!("description: {}", yaml_string(&summary)),
+    ];
+    lines.extend(keywords_line(&plan.major_name));
+    lines.extend(["---".to_string(), "".to_string(), format!("{}。", summary)]);
+
+    // Semester cards, in chronological order
+    let mut cards: Vec<(Semester, Vec<&Course>)> = Vec::new();
+    for course in &with_page {
+        for semester in semesters.placements(course.recommended_semester.as_deref()) {
+            match cards.iter_mut().find(|(s, _)| *s == semester) {
+                Some((_, group)) => group.push(course),
+                None => cards.push((semester, vec![course])),
+            }
+        }
+    }
+    cards.sort_by_key(|(semester, _)| semester.order);
+
+    if !cards.is_empty() {
+        lines.push("".to_string());
+        lines.push("<Cards>".to_string());
+        for (semester, group) in &cards {
+            lines.push(format!(
+                "  <Card title=\"{}\" description=\"{} 门课程 · {} 学分\" href=\"/docs/{}/{}/{}\" />",
+                semester.title,
+                group.len(),
+                format_credit(Some(total_credit(group))),
+                plan.year,
+                plan.major_code,
+                semester.folder
+            ));
+        }
+        lines.push("</Cards>".to_string());
+    }
+
+    // Credits per semester
+    let mut by_semester: Vec<(Semester, Vec<&Course>)> = Vec::new();
+    for course in &courses {
+        let semester = semesters.place(course.recommended_semester.as_deref());
+        match by_semester.iter_mut().find(|(s, _)| *s == semester) {
+            Some((_, group)) => group.push(course),
+            None => by_semester.push((semester, vec![course])),
+        }
+    }
+    by_semester.sort_by_key(|(semester, _)| semester.order);
+
+    lines.extend([
+        "".to_string(),
+        "## 学期学分".to_string(),
+        "".to_string(),
+        "| 学期 | 课程数 | 学分 |".to_string(),
+        "| --- | --- | --- |".to_string(),
+    ]);
+    for (semester, group) in &by_semester {
+        lines.push(format!(
+            "| {} | {} | {} |",
+            escape_table_cell(&semester.title),
+            group.len(),
+            format_credit(Some(total_credit(group)))
+        ));
+    }
+    lines.push(format!(
+        "| 合计 | {} | {} |",
+        courses.len(),
+        format_credit(Some(total_credit(&courses)))
+    ));
+
+    // Credits per course nature
+    lines.extend([
+        "".to_string(),
+        "## 课程性质".to_string(),
+        "".to_string(),
+        "| 课程性质 | 课程数 | 学分 |".to_string(),
+        "| --- | --- | --- |".to_string(),
+    ]);
+    for (nature, group) in group_by_nature(&courses) {
+        lines.push(format!(
+            "| {} | {} | {} |",
+            escape_table_cell(nature),
+            group.len(),
+            format_credit(Some(total_credit(&group)))
+        ));
+    }
+
+    // Hour distribution totals
+    let hours: Vec<&HourDistribution> = courses.iter().filter_map(|c| c.hours.as_ref()).collect();
+    let sum = |field: fn(&HourDistribution) -> Option<u32>| -> u32 {
+        hours.iter().filter_map(|h| field(h)).sum()
+    };
+    let totals = [
+        sum(|h| h.theory),
+        sum(|h| h.lab),
+        sum(|h| h.practice),
+        sum(|h| h.exercise),
+        sum(|h| h.computer),
+        sum(|h| h.tutoring),
+    ];
+    lines.extend([
+        "".to_string(),
+        "## 学时分布".to_string(),
+        "".to_string(),
+        "| 理论 | 实验 | 实践 | 习题 | 上机 | 辅导 | 合计 |".to_string(),
+        "| --- | --- | --- | --- | --- | --- | --- |".to_string(),
+        format!(
+            "| {} | {} |",
+            totals.map(|t| t.to_string()).join(" | "),
+            totals.iter().sum::<u32>()
+        ),
+    ]);
+
+    // Resource coverage
+    lines.extend([
+        "".to_string(),
+        "## 课程资料".to_string(),
+        "".to_string(),
+        format!(
+            "{} 门课程有资料，{} 门课程暂无资料。",
+            with_files,
+            courses.len() - with_files
+        ),
+    ]);
+
+    // Full course table in semester order
+    let mut table: Vec<(Semester, &Course)> = courses
+        .iter()
+        .map(|c| (semesters.place(c.recommended_semester.as_deref()), *c))
+        .collect();
+    table.sort_by(|(a, x), (b, y)| a.order.cmp(&b.order).then_with(|| x.code.cmp(&y.code)));
+
+    // Rows of the sortable table component, initially in semester order
+    lines.extend([
+        "".to_string(),
+        "## 全部课程".to_string(),
+        "".to_string(),
+        "<CourseTable".to_string(),
+        "  courses={[".to_string(),
+    ]);
+    let rows: Vec<String> = table
+        .into_iter()
+        .map(|(_, course)| {
+            let href = has_page(course).then(|| plan_course_href(plan, course, semesters));
+            let titles: Vec<String> = semesters
+                .placements(course.recommended_semester.as_deref())
+                .into_iter()
+                .map(|s| s.title)
+                .collect();
+            let row = serde_json::json!({
+                "code": course.code,
+                "name": course.name,
+                "href": href,
+                "semester": titles.join("、"),
+                "credit": course.credit,
+                "nature": course.course_nature,
+                "assessment": course.assessment_method,
+                "files": file_count(course),
+            });
+            format!("    {}", row)
+        })
+        .collect();
+    if !rows.is_empty() {
+        lines.push(rows.join(",\n"));
+    }
+    lines.extend(["  ]}".to_string(), "/>".to_string()]);
+
+    lines.join("\n")
+}
+
+/// Count the resource files of every course of the plans, after exclusions.
+///
+/// Courses outside repos_list (if repos_list.txt exists) count as having none.
+fn count_resource_files<'a>(
+    plans: impl IntoIterator<Item = &'a Plan>,
+    repos_set: &HashSet<String>,
+    trees: &FileTrees,
+) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for course in plans.into_iter().flat_map(|plan| &plan.courses) {
+        if counts.contains_key(&course.code)
+            || !(repos_set.is_empty() || repos_set.contains(&course.code))
+        {
+            continue;
+        }
+        let count = trees
+            .get(&course.code)
+            .map_or(0, |tree| summarize_files(&tree.nodes).0);
+        counts.insert(course.code.clone(), count);
+    }
+    counts
+}
+
+/// Check whether a course gets a canonical page: it must have a fetched README
+/// and be listed in repos_list (if repos_list.txt exists)
+pub fn has_course_page(code: &str, repos_dir: &Path, repos_set: &HashSet<String>) -> bool {
+    (repos_set.is_empty() || repos_set.contains(code))
+        && repos_dir.join(format!("{}.mdx", code)).exists()
+}
+
+/// Collect every course that gets a canonical page.
+///
+/// A course may appear in several plans; the most recent plan including it
+/// provides the name, as in [`crate::models::latest_course_names`].
+fn collect_courses<'a>(
+    plans: &'a [Plan],
+    repos_dir: &Path,
+    repos_set: &HashSet<String>,
+) -> Vec<&'a Course> {
+    let mut ordered: Vec<&Plan> = plans.iter().collect();
+    ordered.sort_by(|a, b| a.cmp_recency(b));
+    let mut seen = HashSet::new();
+
+    ordered
+        .into_iter()
+        .flat_map(|plan| &plan.courses)
+        .filter(|course| {
+            has_course_page(&course.code, repos_dir, repos_set) && seen.insert(course.code.as_str())
+        })
+        .collect()
+}
+
+/// README text shared by the canonical and plan-specific pages of a course
+#[derive(Debug, Default)]
+struct CourseText {
+    /// README frontmatter `title`, overriding the course name
+    title: Option<String>,
+    /// README H1, used for unnamed courses
+    heading: Option<String>,
+    /// README frontmatter `description`, or the first paragraph of the README body
+    description: String,
+}
+
+impl CourseText {
+    fn from_readme(readme: &ReadmeParts) -> Self {
+        Self {
+            title: readme
+                .frontmatter
+                .get("title")
+                .and_then(|v| v.as_str().map(str::to_string)),
+            heading: readme.title.clone(),
+            description: readme
+                .frontmatter
+                .get("description")
+                .and_then(|v| v.as_str().map(str::to_string))
+                .or_else(|| extract_description(&readme.body))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Page title of a course: the frontmatter title, the course name, the
+    /// README's H1 for unnamed courses, or the course code
+    fn page_title(&self, course: &Course) -> String {
+        self.title.clone().unwrap_or_else(|| {
+            if course.name.trim().is_empty() {
+                self.heading.clone().unwrap_or_else(|| course.code.clone())
+            } else {
+                course.name.clone()
+            }
+        })
+    }
+}
+
+/// Read the README text of a course without writing its page
+fn read_course_text(code: &str, repos_dir: &Path) -> Result<CourseText> {
+    let readme_content = fs::read_to_string(repos_dir.join(format!("{}.mdx", code)))?;
+    Ok(CourseText::from_readme(&split_readme(&readme_content)))
+}
+
+/// Index every plan including each course
+pub fn index_occurrences(plans: &[Plan]) -> HashMap<&str, Vec<(&Plan, &Course)>> {
+    let mut occurrences: HashMap<&str, Vec<(&Plan, &Course)>> = HashMap::new();
+    for plan in plans {
+        for course in &plan.courses {
+            occurrences
+                .entry(course.code.as_str())
+                .or_default()
+                .push((plan, course));
+        }
+    }
+    occurrences
+}
+
+/// Write the canonical page of a course holding its README, the plans including
+/// it and its file tree.
+///
+/// Returns the README text so plan-specific pages can reuse it.
+#[allow(clippy::too_many_arguments)]
+fn write_course_page(
+    course: &Course,
+    occurrences: &[(&Plan, &Course)],
+    config: &Config,
+    trees: &FileTrees,
+    repos_dir: &Path,
+    courses_dir: &Path,
+    course_pages: &HashMap<String, String>,
+    assets: Option<&AssetMirror>,
+) -> Result<CourseText> {
+    let mdx_path = repos_dir.join(format!("{}.mdx", course.code));
+
+    // Read README content without its title and frontmatter
+    let readme_content = fs::read_to_string(&mdx_path)?;
+    let mut readme = split_readme(&readme_content);
+    let mut content = rewrite_links(&readme.body, &course.code, |code| {
+        course_pages.get(code).cloned()
+    });
+    if let Some(mirror) = assets {
+        content = mirror.rewrite(&content);
+    }
+
+    // README frontmatter `file_sort` overrides the configured file order
+    let order = readme
+        .frontmatter
+        .remove("file_sort")
+        .and_then(|v| match serde_yaml::from_value(v) {
+            Ok(order) => Some(order),
+            Err(e) => {
+                eprintln!(
+                    "Warning: Ignoring invalid file_sort in README of {}: {}",
+                    course.code, e
+                );
+                None
+            }
+        })
+        .unwrap_or_else(|| config.file_tree.order(&course.code));
+
+    // Generate file tree from worktree.json
+    let filetree_content = if let Some(file_tree) = trees.get(&course.code) {
+        let mut tree = file_tree.nodes.clone();
+        sort_tree(&mut tree, order);
+        let jsx = tree_to_jsx(&tree, 1, &config.file_tree);
+        let mut content = format!(
+            "\n\n## 资源下载\n\n<Files url=\"https://open.osa.moe/openauto/{}\">\n{}\n</Files>",
+            course.code, jsx
+        );
+        if config.file_tree.category_view && !tree.is_empty() {
+            content.push_str(&format!(
+                "\n\n### 按类别浏览\n\n<Files url=\"https://open.osa.moe/openauto/{}\">\n{}\n</Files>",
+                course.code,
+                category_view_jsx(&tree, 1)
+            ));
+        }
+        content
+    } else {
+        String::new()
+    };
+
+    // README frontmatter may override title and description
+    let text = CourseText::from_readme(&readme);
+    let title = text.page_title(course);
+    readme.frontmatter.remove("title");
+    readme.frontmatter.remove("description");
+
+    // Keywords from the README frontmatter come before the generated ones
+    let mut keywords: Vec<String> = match readme.frontmatter.remove("keywords") {
+        Some(serde_yaml::Value::Sequence(items)) => items
+            .iter()
+            .filter_map(|v| v.as_str().map(str::to_string))
+            .collect(),
+        Some(serde_yaml::Value::String(keyword)) => vec![keyword],
+        _ => Vec::new(),
+    };
+    for keyword in pinyin::keywords(&title) {
+        if !keywords.contains(&keyword) {
+            keywords.push(keyword);
+        }
+    }
+
+    let frontmatter = CoursePageFrontmatter {
+        title,
+        description: text.description.clone(),
+        keywords,
+    };
+
+    let plans_table = if occurrences.is_empty() {
+        String::new()
+    } else {
+        format!("\n\n{}", build_plans_table(occurrences, &config.semesters))
+    };
+
+    let page_content = format!(
+        "{}\n\n{}{}{}",
+        frontmatter.to_yaml_with(&readme.frontmatter),
+        content,
+        plans_table,
+        filetree_content
+    );
+    fs::write(
+        courses_dir.join(format!("{}.mdx", course.code)),
+        page_content,
+    )?;
+
+    Ok(text)
+}
+
+/// Discover and mirror the images referenced from the READMEs of some courses
+async fn mirror_readme_images(
+    courses: &[&Course],
+    repos_dir: &Path,
+    mirror: &mut AssetMirror,
+) -> Result<()> {
+    let mut image_urls = Vec::new();
+    for course in courses {
+        let readme_content = fs::read_to_string(repos_dir.join(format!("{}.mdx", course.code)))?;
+        let content = rewrite_links(&readme_content, &course.code, |_| None);
+        image_urls.extend(discover_images(&content));
+    }
+
+    let mirrored = mirror.mirror_all(&image_urls, MIRROR_CONCURRENCY).await?;
+    println!("Mirrored {} new images", mirrored);
+    Ok(())
+}
+
+/// Write the `courses/` section metadata
+fn write_courses_meta(courses_dir: &Path) -> Result<()> {
+    fs::create_dir_all(courses_dir)?;
+    let courses_meta = serde_json::json!({
+        "title": "全部课程",
+        "root": true,
+    });
+    fs::write(
+        courses_dir.join("meta.json"),
+        serde_json::to_string_pretty(&courses_meta)?,
+    )?;
+    Ok(())
+}
+
+/// Write the pages of a plan: plan-specific course pages, semester indexes, the
+/// major metadata and the plan overview.
+///
+/// `texts` maps every course with a canonical page to its README text; other
+/// courses get no plan-specific page.
+fn write_plan_pages(
+    plan: &Plan,
+    docs_dir: &Path,
+    texts: &HashMap<String, CourseText>,
+    file_counts: &HashMap<String, usize>,
+    config: &Config,
+) -> Result<()> {
+    let major_dir = docs_dir.join(&plan.year).join(&plan.major_code);
+    fs::create_dir_all(&major_dir)?;
+
+    // Track courses by semester for this major
+    let mut courses_by_semester: HashMap<Semester, Vec<&Course>> = HashMap::new();
+
+    // Process each course
+    for course in &plan.courses {
+        let Some(text) = texts.get(&course.code) else {
+            continue;
+        };
+
+        // Determine semesters of the course; courses without a recognised
+        // semester go into the unassigned folder. The page is written once into
+        // the first semester and referenced from every semester index.
+        let placements = config
+            .semesters
+            .placements(course.recommended_semester.as_deref());
+        let target_dir = major_dir.join(&placements[0].folder);
+        fs::create_dir_all(&target_dir)?;
+        for semester in placements {
+            fs::create_dir_all(major_dir.join(&semester.folder))?;
+            courses_by_semester
+                .entry(semester)
+                .or_default()
+                .push(course);
+        }
+
+        // Build frontmatter with the metadata of this plan
+        let frontmatter = build_frontmatter(&text.page_title(course), &text.description, course);
+
+        // Write plan-specific course page linking to the canonical page
+        let page_content = format!(
+            "{}\n\n<CourseInfo />\n\n<Cards>\n  <Card title=\"课程资料与资源下载\" href=\"{}\" />\n</Cards>",
+            frontmatter,
+            course_page_href(&course.code)
+        );
+        fs::write(
+            target_dir.join(format!("{}.mdx", course.code)),
+            page_content,
+        )?;
+    }
+
+    // Generate semester index pages
+    for (semester, courses) in &courses_by_semester {
+        let index = build_semester_index(
+            &semester.title,
+            courses,
+            config.semester_index.sort,
+            |course| plan_course_href(plan, course, &config.semesters),
+        );
+        fs::write(major_dir.join(&semester.folder).join("index.mdx"), index)?;
+    }
+
+    // Semester folders with course pages, in chronological order
+    let mut semester_folders: Vec<&Semester> = courses_by_semester.keys().collect();
+    semester_folders.sort_by_key(|s| s.order);
+
+    // Write major metadata
+    let pages: Vec<String> = std::iter::once("...".to_string())
+        .chain(semester_folders.iter().map(|s| s.folder.clone()))
+        .collect();
+
+    let major_meta = serde_json::json!({
+        "title": plan.major_name,
+        "root": true,
+        "defaultOpen": true,
+        "pages": pages,
+    });
+    fs::write(
+        major_dir.join("meta.json"),
+        serde_json::to_string_pretty(&major_meta)?,
+    )?;
+
+    // Generate major overview page
+    let overview = build_plan_overview(
+        plan,
+        &config.semesters,
+        |course| texts.contains_key(&course.code),
+        |course| file_counts.get(&course.code).copied().unwrap_or(0),
+    );
+    fs::write(major_dir.join("index.mdx"), overview)?;
+
+    Ok(())
+}
+
+/// Write the metadata and index page of a year listing the plans of that year
+fn write_year_index(year: &str, plans: &[Plan], docs_dir: &Path) -> Result<()> {
+    let year_dir = docs_dir.join(year);
+    fs::create_dir_all(&year_dir)?;
+    let year_meta = serde_json::json!({"title": year});
+    fs::write(
+        year_dir.join("meta.json"),
+        serde_json::to_string_pretty(&year_meta)?,
+    )?;
+
+    // Generate year index with major cards
+    let mut year_index = vec![
+        "---".to_string(),
+        "title: 目录".to_string(),
+        "---".to_string(),
+        "".to_string(),
+        "<Cards>".to_string(),
+    ];
+
+    for plan in plans.iter().filter(|p| p.year == year) {
+        year_index.push(format!(
+            "  <Card title=\"{}\" href=\"/docs/{}/{}\" />",
+            plan.major_name, year, plan.major_code
+        ));
+    }
+    year_index.push("</Cards>".to_string());
+
+    fs::write(year_dir.join("index.mdx"), year_index.join("\n"))?;
+    Ok(())
+}
+
 /// Generate all course pages and index pages
+///
+/// Each course gets one canonical page under `courses/` holding its README and
+/// file tree. The pages under `<year>/<major>/<semester>/` only carry the
+/// plan-specific metadata and link to the canonical page.
+///
+/// When an asset mirror is given, images referenced from course READMEs are
+/// downloaded first and the pages point at the local copies.
 pub async fn generate_course_pages(
     plans: &[Plan],
     repos_dir: &Path,
     docs_dir: &Path,
     repos_set: &HashSet<String>,
+    trees: &FileTrees,
+    config: &Config,
+    mut assets: Option<&mut AssetMirror>,
 ) -> Result<()> {
-    let mut years: HashSet<String> = HashSet::new();
-    let mut majors_by_year: HashMap<String, Vec<(String, String)>> = HashMap::new();
+    let courses = collect_courses(plans, repos_dir, repos_set);
+    let course_pages: HashMap<String, String> = courses
+        .iter()
+        .map(|c| (c.code.clone(), course_page_href(&c.code)))
+        .collect();
+
+    // Discover and mirror README images before any page is written
+    if let Some(mirror) = assets.as_deref_mut() {
+        mirror_readme_images(&courses, repos_dir, mirror).await?;
+    }
 
+    // Generate canonical course pages
+    let courses_dir = docs_dir.join("courses");
+    write_courses_meta(&courses_dir)?;
+
+    let occurrences = index_occurrences(plans);
+    let mut texts: HashMap<String, CourseText> = HashMap::new();
+    for course in &courses {
+        let text = write_course_page(
+            course,
+            occurrences
+                .get(course.code.as_str())
+                .map(Vec::as_slice)
+                .unwrap_or_default(),
+            config,
+            trees,
+            repos_dir,
+            &courses_dir,
+            &course_pages,
+            assets.as_deref(),
+        )?;
+        texts.insert(course.code.clone(), text);
+    }
+
+    let file_counts = count_resource_files(plans, repos_set, trees);
+    let mut years: BTreeSet<&str> = BTreeSet::new();
     for plan in plans {
-        years.insert(plan.year.clone());
+        years.insert(&plan.year);
+        write_plan_pages(plan, docs_dir, &texts, &file_counts, config)?;
+    }
+
+    // Generate year index pages
+    for year in years {
+        write_year_index(year, plans, docs_dir)?;
+    }
 
-        majors_by_year
-            .entry(plan.year.clone())
-            .or_default()
-            .push((plan.major_code.clone(), plan.major_name.clone()));
+    Ok(())
+}
 
-        let major_dir = docs_dir.join(&plan.year).join(&plan.major_code);
-        fs::create_dir_all(&major_dir)?;
+/// Courses and plans whose pages must be rewritten, see [`update_course_pages`]
+#[derive(Debug, Default)]
+pub struct PageUpdate {
+    /// Codes of courses whose README or worktree changed
+    pub courses: BTreeSet<String>,
+    /// `(year, major_code)` of plans that were added, changed or removed
+    pub plans: BTreeSet<(String, String)>,
+    /// Previous version of the changed and removed plans
+    pub previous: Vec<Plan>,
+}
 
-        // Write major metadata
-        let pages: Vec<String> = std::iter::once("...".to_string())
-            .chain(
-                SEMESTER_MAPPING
+/// Paths touched by [`update_course_pages`]
+#[derive(Debug, Default, PartialEq)]
+pub struct UpdatedPages {
+    /// Canonical pages, plan directories and year indexes that were rewritten
+    pub written: Vec<PathBuf>,
+    /// Pages and directories of courses and plans that no longer exist
+    pub removed: Vec<PathBuf>,
+}
+
+/// Semester folders and plan-specific pages of the courses of a plan accepted by
+/// `include`, as written by [`write_plan_pages`]
+fn plan_outputs<F: Fn(&Course) -> bool>(
+    plan: &Plan,
+    docs_dir: &Path,
+    semesters: &SemestersConfig,
+    include: F,
+) -> (BTreeSet<PathBuf>, BTreeSet<PathBuf>) {
+    let major_dir = docs_dir.join(&plan.year).join(&plan.major_code);
+    let mut folders = BTreeSet::new();
+    let mut pages = BTreeSet::new();
+    for course in plan.courses.iter().filter(|c| include(c)) {
+        let placements = semesters.placements(course.recommended_semester.as_deref());
+        pages.insert(
+            major_dir
+                .join(&placements[0].folder)
+                .join(format!("{}.mdx", course.code)),
+        );
+        folders.extend(placements.iter().map(|s| major_dir.join(&s.folder)));
+    }
+    (folders, pages)
+}
+
+/// Remove a generated file or directory, returning whether it existed
+fn remove_output(path: &Path) -> Result<bool> {
+    let result = if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    };
+    match result {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+impl PageUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.courses.is_empty() && self.plans.is_empty()
+    }
+}
+
+/// Rewrite only the pages affected by an update.
+///
+/// Canonical pages are rewritten for the updated courses and for every course of
+/// the updated plans, before and after the update; plan pages for the updated
+/// plans and for every plan including an updated course, along with the index of
+/// their years. Pages of courses that lost their README or left a plan, and the
+/// directories of removed plans, are deleted.
+#[allow(clippy::too_many_arguments)]
+pub async fn update_course_pages(
+    plans: &[Plan],
+    update: &PageUpdate,
+    repos_dir: &Path,
+    docs_dir: &Path,
+    repos_set: &HashSet<String>,
+    trees: &FileTrees,
+    config: &Config,
+    mut assets: Option<&mut AssetMirror>,
+) -> Result<UpdatedPages> {
+    let courses = collect_courses(plans, repos_dir, repos_set);
+    let course_pages: HashMap<String, String> = courses
+        .iter()
+        .map(|c| (c.code.clone(), course_page_href(&c.code)))
+        .collect();
+
+    let is_updated = |plan: &Plan| {
+        update
+            .plans
+            .contains(&(plan.year.clone(), plan.major_code.clone()))
+    };
+    let affected_plans: Vec<&Plan> = plans
+        .iter()
+        .filter(|plan| {
+            is_updated(plan)
+                || plan
+                    .courses
                     .iter()
-                    .map(|(_, folder, _)| folder.to_string()),
-            )
-            .collect();
-
-        let major_meta = serde_json::json!({
-            "title": plan.major_name,
-            "root": true,
-            "defaultOpen": true,
-            "pages": pages,
-        });
-        fs::write(
-            major_dir.join("meta.json"),
-            serde_json::to_string_pretty(&major_meta)?,
-        )?;
+                    .any(|c| update.courses.contains(&c.code))
+        })
+        .collect();
+
+    // Canonical pages list the plans including the course, so courses of updated
+    // plans are rewritten too, including the ones they dropped
+    let mut rewritten_codes: HashSet<&str> = update.courses.iter().map(String::as_str).collect();
+    for plan in plans.iter().filter(|plan| is_updated(plan)) {
+        rewritten_codes.extend(plan.courses.iter().map(|c| c.code.as_str()));
+    }
+    for plan in &update.previous {
+        rewritten_codes.extend(plan.courses.iter().map(|c| c.code.as_str()));
+    }
+    let rewritten: Vec<&Course> = courses
+        .into_iter()
+        .filter(|course| rewritten_codes.contains(course.code.as_str()))
+        .collect();
+
+    // Courses left without a canonical page lose it
+    let mut pages = UpdatedPages::default();
+    let courses_dir = docs_dir.join("courses");
+    let mut removed_codes: Vec<&str> = rewritten_codes
+        .iter()
+        .copied()
+        .filter(|code| !course_pages.contains_key(*code))
+        .collect();
+    removed_codes.sort_unstable();
+    for code in removed_codes {
+        let path = courses_dir.join(format!("{}.mdx", code));
+        if remove_output(&path)? {
+            pages.removed.push(path);
+        }
+    }
 
-        // Track courses by semester for this major
-        let mut courses_by_semester: HashMap<String, Vec<(String, String)>> = HashMap::new();
+    if let Some(mirror) = assets.as_deref_mut() {
+        mirror_readme_images(&rewritten, repos_dir, mirror).await?;
+    }
+
+    write_courses_meta(&courses_dir)?;
+
+    let occurrences = index_occurrences(plans);
+    let mut texts: HashMap<String, CourseText> = HashMap::new();
+    for course in &rewritten {
+        let text = write_course_page(
+            course,
+            occurrences
+                .get(course.code.as_str())
+                .map(Vec::as_slice)
+                .unwrap_or_default(),
+            config,
+            trees,
+            repos_dir,
+            &courses_dir,
+            &course_pages,
+            assets.as_deref(),
+        )?;
+        texts.insert(course.code.clone(), text);
+        pages
+            .written
+            .push(courses_dir.join(format!("{}.mdx", course.code)));
+    }
 
-        // Process each course
+    // Plan pages also need the README text of the courses left untouched
+    for plan in &affected_plans {
         for course in &plan.courses {
-            // Only process courses that exist in repos_list (if repos_list.txt exists)
-            if !repos_set.is_empty() && !repos_set.contains(&course.code) {
-                continue;
+            if course_pages.contains_key(&course.code) && !texts.contains_key(&course.code) {
+                let text = read_course_text(&course.code, repos_dir)?;
+                texts.insert(course.code.clone(), text);
             }
+        }
+    }
 
-            let mdx_path = repos_dir.join(format!("{}.mdx", course.code));
-            let json_path = repos_dir.join(format!("{}.json", course.code));
+    let file_counts = count_resource_files(affected_plans.iter().copied(), repos_set, trees);
+    for plan in &affected_plans {
+        write_plan_pages(plan, docs_dir, &texts, &file_counts, config)?;
+        pages
+            .written
+            .push(docs_dir.join(&plan.year).join(&plan.major_code));
 
-            if !mdx_path.exists() {
-                continue;
+        // Drop the pages and semester folders the plan no longer has, comparing
+        // with its previous version if it changed
+        let previous = update
+            .previous
+            .iter()
+            .find(|p| p.year == plan.year && p.major_code == plan.major_code)
+            .unwrap_or(plan);
+        let (old_folders, old_pages) =
+            plan_outputs(previous, docs_dir, &config.semesters, |_| true);
+        let (folders, plan_pages) = plan_outputs(plan, docs_dir, &config.semesters, |course| {
+            texts.contains_key(&course.code)
+        });
+        for path in old_pages.difference(&plan_pages) {
+            if remove_output(path)? {
+                pages.removed.push(path.clone());
             }
+        }
+        for path in old_folders.difference(&folders) {
+            if remove_output(path)? {
+                pages.removed.push(path.clone());
+            }
+        }
+    }
 
-            // Read README content (skip first 2 lines which are title)
-            let readme_content = fs::read_to_string(&mdx_path)?;
-            let content_lines: Vec<&str> = readme_content.lines().skip(2).collect();
-            let content = content_lines.join("\n");
-
-            // Determine target directory based on semester
-            let target_dir = if let Some(ref sem) = course.recommended_semester {
-                if let Some((folder, _title)) = get_semester_folder(sem) {
-                    let sem_dir = major_dir.join(folder);
-                    fs::create_dir_all(&sem_dir)?;
-                    courses_by_semester
-                        .entry(folder.to_string())
-                        .or_default()
-                        .push((course.code.clone(), course.name.clone()));
-                    sem_dir
-                } else {
-                    major_dir.clone()
-                }
-            } else {
-                major_dir.clone()
-            };
+    // Removed plans lose their directory, and their year too once it is empty;
+    // other years of removed plans only need their index rewritten
+    let mut years: BTreeSet<&str> = affected_plans.iter().map(|p| p.year.as_str()).collect();
+    for (year, major_code) in &update.plans {
+        if plans
+            .iter()
+            .any(|p| p.year == *year && p.major_code == *major_code)
+        {
+            continue;
+        }
+        let (plan_dir, year_dir) = (docs_dir.join(year).join(major_code), docs_dir.join(year));
+        if remove_output(&plan_dir)? {
+            pages.removed.push(plan_dir);
+        }
+        if plans.iter().any(|p| p.year == *year) {
+            years.insert(year);
+        } else if remove_output(&year_dir)? {
+            pages.removed.push(year_dir);
+        }
+    }
 
-            // Generate file tree from worktree.json
-            let filetree_content = if json_path.exists() {
-                let json_content = fs::read_to_string(&json_path)?;
-                let worktree: WorktreeData = serde_json::from_str(&json_content)?;
-                let tree = build_file_tree(&worktree, &course.code);
-                let jsx = tree_to_jsx(&tree, 1);
-                format!(
-                    "\n\n## 资源下载\n\n<Files url=\"https://open.osa.moe/openauto/{}\">\n{}\n</Files>",
-                    course.code, jsx
-                )
-            } else {
-                String::new()
-            };
+    for year in years {
+        write_year_index(year, plans, docs_dir)?;
+        pages.written.push(docs_dir.join(year).join("index.mdx"));
+    }
 
-            // Build frontmatter
-            let frontmatter = build_frontmatter(&course.name, course);
-
-            // Write course page
-            let page_content = format!(
-                "{}\n\n<CourseInfo />\n\n{}{}",
-                frontmatter, content, filetree_content
-            );
-            fs::write(
-                target_dir.join(format!("{}.mdx", course.code)),
-                page_content,
-            )?;
-        }
+    Ok(pages)
+}
 
-        // Generate semester index pages
-        for (folder, courses) in &courses_by_semester {
-            let sem_dir = major_dir.join(folder);
-            let sem_title = SEMESTER_MAPPING
-                .iter()
-                .find(|(_, f, _)| f == folder)
-                .map(|(_, _, t)| *t)
-                .unwrap_or(folder.as_str());
-
-            let mut cards = vec![
-                "---".to_string(),
-                format!("title: {}", sem_title),
-                "---".to_string(),
-                "".to_string(),
-                "<Cards>".to_string(),
-            ];
-
-            for (code, name) in courses {
-                cards.push(format!(
-                    "  <Card title=\"{}\" href=\"/docs/{}/{}/{}/{}\" />",
-                    name, plan.year, plan.major_code, folder, code
-                ));
-            }
-            cards.push("</Cards>".to_string());
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            fs::write(sem_dir.join("index.mdx"), cards.join("\n"))?;
-        }
+    use crate::ignore::IgnoreRules;
+    use crate::loader::load_file_trees;
+    use crate::models::test_support::plan;
 
-        // Generate major index page with semester cards
-        let mut major_index = vec![
-            "---".to_string(),
-            "title: 目录".to_string(),
-            "---".to_string(),
-            "".to_string(),
-            "<Cards>".to_string(),
-        ];
+    fn course(code: &str, semester: Option<&str>, credit: Option<f64>) -> Course {
+        let mut course = crate::models::test_support::course(code)
+            .with_name("Test")
+            .with_nature("必修");
+        course.recommended_semester = semester.map(str::to_string);
+        course.credit = credit;
+        course
+    }
 
-        for (folder, title) in SEMESTER_MAPPING.iter().map(|(_, f, t)| (f, t)) {
-            major_index.push(format!(
-                "  <Card title=\"{}\" href=\"/docs/{}/{}/{}\" />",
-                title, plan.year, plan.major_code, folder
-            ));
+    #[test]
+    fn test_format_credit() {
+        assert_eq!(format_credit(Some(3.0)), "3");
+        assert_eq!(format_credit(Some(2.5)), "2.5");
+        assert_eq!(
+            format_credit_range([Some(5.0), None, Some(5.5), Some(5.0)]),
+            "5–5.5"
+        );
+        assert_eq!(format_credit_range([Some(3.0), Some(3.0)]), "3");
+        assert_eq!(format_credit_range([None]), "—");
+        assert_eq!(format_credit(None), "—");
+    }
+
+    #[test]
+    fn test_yaml_string() {
+        for value in ["a: b # c", "\"quoted", "'single", "- item", "多行\n文本"] {
+            let parsed: serde_yaml::Value =
+                serde_yaml::from_str(&format!("description: {}", yaml_string(value))).unwrap();
+            assert_eq!(parsed["description"].as_str(), Some(value));
         }
-        major_index.push("</Cards>".to_string());
+    }
 
-        fs::write(major_dir.join("index.mdx"), major_index.join("\n"))?;
+    #[test]
+    fn test_keywords_line() {
+        assert_eq!(
+            keywords_line("计算机科学与技术").as_deref(),
+            Some("keywords: [jisuanjikexueyujishu, jsjkxyjs]")
+        );
+        assert_eq!(keywords_line("Major 080901"), None);
     }
 
-    // Generate year index pages
-    for year in &years {
-        let year_dir = docs_dir.join(year);
-        let year_meta = serde_json::json!({"title": year});
+    #[test]
+    fn test_plan_course_href() {
+        let semesters = SemestersConfig::default();
+        let p = plan("2023", "080901", vec![]);
+        let with_semester = course("TEST101", Some("第一学年秋季"), None);
+        let without_semester = course("TEST102", None, None);
+
+        assert_eq!(
+            plan_course_href(&p, &with_semester, &semesters),
+            "/docs/2023/080901/fresh-autumn/TEST101"
+        );
+        assert_eq!(
+            plan_course_href(&p, &without_semester, &semesters),
+            "/docs/2023/080901/other/TEST102"
+        );
+
+        // Courses spanning several semesters live in their first semester
+        let spanning = course("TEST103", Some("第一学年春季、第二学年秋季"), None);
+        assert_eq!(
+            plan_course_href(&p, &spanning, &semesters),
+            "/docs/2023/080901/fresh-spring/TEST103"
+        );
+    }
+
+    #[test]
+    fn test_build_semester_index_groups_and_totals() {
+        let mut a = course("MATH1001", None, Some(5.5));
+        a.assessment_method = Some("考试".to_string());
+        let b = course("COMP1001", None, Some(3.0));
+        let mut c = course("ELEC1001", None, Some(2.0));
+        c.course_nature = Some("选修".to_string());
+        let mut d = course("OTHR1001", None, None);
+        d.course_nature = None;
+
+        let index = build_semester_index(
+            "大一·秋",
+            &[&a, &c, &d, &b],
+            CourseOrder::Code,
+            |course| format!("/docs/2023/080901/fresh-autumn/{}", course.code),
+        );
+
+        assert!(index
+            .contains("title: \"大一·秋\"\ndescription: \"本学期共 4 门课程，合计 10.5 学分\""));
+        let required = index.find("## 必修（8.5 学分）").unwrap();
+        let elective = index.find("## 选修（2 学分）").unwrap();
+        let other = index.find("## 其他（0 学分）").unwrap();
+        assert!(required < elective && elective < other);
+
+        // Sorted by code within the group
+        assert!(index.find("COMP1001").unwrap() < index.find("MATH1001").unwrap());
+        assert!(index.contains(
+            "<Card title=\"Test\" description=\"5.5 学分 · 考试\" href=\"/docs/2023/080901/fresh-autumn/MATH1001\" />"
+        ));
+        // No credit detail for courses without a credit
+        assert!(!index.contains("— 学分"));
+        assert!(index
+            .contains("<Card title=\"Test\" href=\"/docs/2023/080901/fresh-autumn/OTHR1001\" />"));
+    }
+
+    #[test]
+    fn test_build_semester_index_sort_by_credit() {
+        let a = course("AAAA1001", None, Some(1.0));
+        let b = course("BBBB1001", None, Some(4.0));
+
+        let index = build_semester_index("t", &[&a, &b], CourseOrder::Credit, |course| {
+            format!("/docs/{}", course.code)
+        });
+        assert!(index.find("BBBB1001").unwrap() < index.find("AAAA1001").unwrap());
+    }
+
+    #[test]
+    fn test_build_plan_overview() {
+        let mut a = course("MATH1001", Some("第一学年秋季"), Some(5.5));
+        a.hours = Some(HourDistribution {
+            theory: Some(80),
+            lab: Some(8),
+            practice: None,
+            exercise: None,
+            computer: None,
+            tutoring: None,
+        });
+        let mut b = course("PE1001", Some("第一学年秋季、第一学年春季"), Some(1.0));
+        b.course_nature = Some("选修".to_string());
+        let c = course("ELEC3001", None, Some(2.0));
+        let p = plan("2023", "080901", vec![c, a, b]);
+
+        let overview = build_plan_overview(
+            &p,
+            &SemestersConfig::default(),
+            |course| course.code != "ELEC3001",
+            |course| if course.code == "PE1001" { 0 } else { 3 },
+        );
+
+        assert!(overview
+            .contains("description: \"2023 级Major 080901培养方案，共 3 门课程，合计 8.5 学分\""));
+
+        // Only semesters with pages get cards, in chronological order
+        let autumn = overview
+            .find("<Card title=\"大一·秋\" description=\"2 门课程 · 6.5 学分\" href=\"/docs/2023/080901/fresh-autumn\" />")
+            .unwrap();
+        let spring = overview.find("<Card title=\"大一·春\"").unwrap();
+        assert!(autumn < spring);
+        assert!(!overview.contains("<Card title=\"未分学期\""));
+        assert!(!overview.contains("大二"));
+
+        // Multi-semester courses count towards their first semester
+        assert!(overview.contains("| 大一·秋 | 2 | 6.5 |"));
+        assert!(overview.contains("| 未分学期 | 1 | 2 |"));
+        assert!(overview.contains("| 合计 | 3 | 8.5 |"));
+        assert!(overview.contains("| 必修 | 2 | 7.5 |\n| 选修 | 1 | 1 |"));
+        assert!(overview.contains("| 80 | 8 | 0 | 0 | 0 | 0 | 88 |"));
+        // Resource coverage counts files, not pages
+        assert!(overview.contains("2 门课程有资料，1 门课程暂无资料。"));
+
+        // The course table is a sortable component, rows in semester order
+        assert!(overview.contains("<CourseTable\n  courses={[\n"));
+        assert!(overview.ends_with("\n  ]}\n/>"));
+        let math = overview
+            .find(r#"    {"assessment":null,"code":"MATH1001","credit":5.5,"files":3,"href":"/docs/2023/080901/fresh-autumn/MATH1001","name":"Test","nature":"必修","semester":"大一·秋"},"#)
+            .unwrap();
+        let pe = overview
+            .find(r#""code":"PE1001","credit":1.0,"files":0,"href":"/docs/2023/080901/fresh-autumn/PE1001","name":"Test","nature":"选修","semester":"大一·秋、大一·春"}"#)
+            .unwrap();
+        let elec = overview
+            .find(r#""code":"ELEC3001","credit":2.0,"files":3,"href":null,"#)
+            .unwrap();
+        assert!(math < pe && pe < elec);
+    }
+
+    #[test]
+    fn test_build_plans_table() {
+        let p2022 = plan("2022", "080901", vec![]);
+        let p2023 = plan("2023", "080902", vec![]);
+        let c2022 = course("TEST101", Some("第一学年秋季"), Some(3.0));
+        let c2023 = course("TEST101", Some("夏季学期"), Some(2.5));
+
+        let table = build_plans_table(
+            &[(&p2022, &c2022), (&p2023, &c2023)],
+            &SemestersConfig::default(),
+        );
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(lines[0], "## 开设情况");
+        assert_eq!(
+            lines[4],
+            "| 2023 | [Major 080902](/docs/2023/080902/summer/TEST101) | 夏季学期 | 2.5 | 必修 |"
+        );
+        assert_eq!(
+            lines[5],
+            "| 2022 | [Major 080901](/docs/2022/080901/fresh-autumn/TEST101) | 大一·秋 | 3 | 必修 |"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_generate_course_pages_titles() {
+        let temp_dir = std::env::temp_dir().join("test_generate_course_pages_titles");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let repos_dir = temp_dir.join("repos");
+        let docs_dir = temp_dir.join("docs");
+        fs::create_dir_all(&repos_dir).unwrap();
+        fs::write(repos_dir.join("TEST201.mdx"), "# 程序设计\n\nBody.").unwrap();
         fs::write(
-            year_dir.join("meta.json"),
-            serde_json::to_string_pretty(&year_meta)?,
-        )?;
+            repos_dir.join("TEST202.mdx"),
+            "---\ntitle: Override\n---\n# Heading\n\nBody.",
+        )
+        .unwrap();
 
-        // Generate year index with major cards
-        if let Some(majors) = majors_by_year.get(year) {
-            let mut year_index = vec![
-                "---".to_string(),
-                "title: 目录".to_string(),
-                "---".to_string(),
-                "".to_string(),
-                "<Cards>".to_string(),
-            ];
-
-            for (code, name) in majors {
-                year_index.push(format!(
-                    "  <Card title=\"{}\" href=\"/docs/{}/{}\" />",
-                    name, year, code
-                ));
-            }
-            year_index.push("</Cards>".to_string());
+        let mut unnamed = course("TEST201", Some("第一学年秋季"), None);
+        unnamed.name = " ".to_string();
+        let plans = vec![plan(
+            "2023",
+            "080901",
+            vec![unnamed, course("TEST202", Some("第一学年秋季"), None)],
+        )];
+        generate_course_pages(
+            &plans,
+            &repos_dir,
+            &docs_dir,
+            &HashSet::new(),
+            &FileTrees::new(),
+            &Config::default(),
+            None,
+        )
+        .await
+        .unwrap();
 
-            fs::write(year_dir.join("index.mdx"), year_index.join("\n"))?;
+        let read = |path: &str| fs::read_to_string(docs_dir.join(path)).unwrap();
+        for (code, title) in [("TEST201", "程序设计"), ("TEST202", "Override")] {
+            let canonical = read(&format!("courses/{}.mdx", code));
+            assert!(canonical.starts_with(&format!("---\ntitle: {}\n", title)));
+            let thin = read(&format!("2023/080901/fresh-autumn/{}.mdx", code));
+            assert!(thin.starts_with(&format!("---\ntitle: {}\n", title)));
+            assert!(thin.contains(&format!(
+                "<Card title=\"课程资料与资源下载\" href=\"/docs/courses/{}\" />",
+                code
+            )));
         }
+
+        let _ = fs::remove_dir_all(&temp_dir);
     }
 
-    Ok(())
+    #[tokio::test]
+    async fn test_update_course_pages() {
+        let temp_dir = std::env::temp_dir().join("test_update_course_pages");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let repos_dir = temp_dir.join("repos");
+        let docs_dir = temp_dir.join("docs");
+        fs::create_dir_all(&repos_dir).unwrap();
+        fs::write(repos_dir.join("TEST101.mdx"), "# A\n\nOld text.").unwrap();
+        fs::write(repos_dir.join("TEST102.mdx"), "# B\n\nOther text.").unwrap();
+
+        let plans = vec![
+            plan(
+                "2023",
+                "080901",
+                vec![course("TEST101", Some("第一学年秋季"), None)],
+            ),
+            plan(
+                "2023",
+                "080902",
+                vec![course("TEST102", Some("第一学年秋季"), None)],
+            ),
+        ];
+        let config = Config::default();
+        let repos_set = HashSet::new();
+        generate_course_pages(
+            &plans,
+            &repos_dir,
+            &docs_dir,
+            &repos_set,
+            &FileTrees::new(),
+            &config,
+            None,
+        )
+        .await
+        .unwrap();
+        let untouched = docs_dir.join("2023/080902/fresh-autumn/TEST102.mdx");
+        fs::write(&untouched, "stale").unwrap();
+
+        fs::write(
+            repos_dir.join("TEST101.mdx"),
+            "---\ntitle: 高等数学\nkeywords: [calculus]\nfile_sort: size\n---\n# A\n\nNew text.",
+        )
+        .unwrap();
+        fs::write(
+            repos_dir.join("TEST101.json"),
+            r#"{"lecture10.pdf": {"size": 20, "time": null}, "lecture2.pdf": {"size": 10, "time": null}}"#,
+        )
+        .unwrap();
+        let update = PageUpdate {
+            courses: BTreeSet::from(["TEST101".to_string()]),
+            ..PageUpdate::default()
+        };
+        let trees = load_file_trees(&plans, &repos_dir, &IgnoreRules::default()).unwrap();
+        let pages = update_course_pages(
+            &plans, &update, &repos_dir, &docs_dir, &repos_set, &trees, &config, None,
+        )
+        .await
+        .unwrap();
+
+        assert!(pages.removed.is_empty());
+        assert_eq!(
+            pages.written,
+            vec![
+                docs_dir.join("courses/TEST101.mdx"),
+                docs_dir.join("2023/080901"),
+                docs_dir.join("2023/index.mdx"),
+            ]
+        );
+        let canonical = fs::read_to_string(docs_dir.join("courses/TEST101.mdx")).unwrap();
+        assert!(canonical.contains("New text."));
+        assert!(canonical.contains("keywords:\n- calculus\n- gaodengshuxue\n- gdsx\n"));
+        assert!(!canonical.contains("file_sort"));
+        // Largest first, as set in the README frontmatter
+        let (lecture2, lecture10) = (
+            canonical.find("lecture2.pdf").unwrap(),
+            canonical.find("lecture10.pdf").unwrap(),
+        );
+        assert!(lecture10 < lecture2);
+        let thin =
+            fs::read_to_string(docs_dir.join("2023/080901/fresh-autumn/TEST101.mdx")).unwrap();
+        assert!(thin.contains("description: New text."));
+        assert_eq!(fs::read_to_string(&untouched).unwrap(), "stale");
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    /// Generate the pages of two courses for plans, in a fresh directory
+    async fn generate_site(name: &str, plans: &[Plan]) -> (PathBuf, PathBuf, PathBuf) {
+        let temp_dir = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&temp_dir);
+        let repos_dir = temp_dir.join("repos");
+        let docs_dir = temp_dir.join("docs");
+        fs::create_dir_all(&repos_dir).unwrap();
+        fs::write(repos_dir.join("TEST101.mdx"), "# A\n\nText.").unwrap();
+        fs::write(repos_dir.join("TEST102.mdx"), "# B\n\nText.").unwrap();
+
+        generate_course_pages(
+            plans,
+            &repos_dir,
+            &docs_dir,
+            &HashSet::new(),
+            &FileTrees::new(),
+            &Config::default(),
+            None,
+        )
+        .await
+        .unwrap();
+        (temp_dir, repos_dir, docs_dir)
+    }
+
+    #[tokio::test]
+    async fn test_update_course_pages_removed_plan() {
+        let kept = plan(
+            "2023",
+            "080901",
+            vec![course("TEST101", Some("第一学年秋季"), None)],
+        );
+        let removed = [
+            plan(
+                "2023",
+                "080902",
+                vec![course("TEST101", Some("第一学年秋季"), None)],
+            ),
+            plan(
+                "2024",
+                "080901",
+                vec![course("TEST102", Some("第一学年秋季"), None)],
+            ),
+        ];
+        let old = vec![kept.clone(), removed[0].clone(), removed[1].clone()];
+        let (temp_dir, repos_dir, docs_dir) =
+            generate_site("test_update_course_pages_removed_plan", &old).await;
+
+        let plans = vec![kept];
+        let update = PageUpdate {
+            plans: removed
+                .iter()
+                .map(|p| (p.year.clone(), p.major_code.clone()))
+                .collect(),
+            previous: removed.to_vec(),
+            ..PageUpdate::default()
+        };
+        let pages = update_course_pages(
+            &plans,
+            &update,
+            &repos_dir,
+            &docs_dir,
+            &HashSet::new(),
+            &FileTrees::new(),
+            &Config::default(),
+            None,
+        )
+        .await
+        .unwrap();
+
+        // TEST102 was only in the removed 2024 plan, the last one of its year
+        assert_eq!(
+            pages.removed,
+            vec![
+                docs_dir.join("courses/TEST102.mdx"),
+                docs_dir.join("2023/080902"),
+                docs_dir.join("2024/080901"),
+                docs_dir.join("2024"),
+            ]
+        );
+        assert!(docs_dir
+            .join("2023/080901/fresh-autumn/TEST101.mdx")
+            .exists());
+        assert!(!docs_dir.join("2024").exists());
+        let year_index = fs::read_to_string(docs_dir.join("2023/index.mdx")).unwrap();
+        assert!(!year_index.contains("080902"));
+        let canonical = fs::read_to_string(docs_dir.join("courses/TEST101.mdx")).unwrap();
+        assert!(!canonical.contains("Major 080902"));
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[tokio::test]
+    async fn test_update_course_pages_dropped_course() {
+        let other = plan(
+            "2023",
+            "080902",
+            vec![course("TEST102", Some("第一学年秋季"), None)],
+        );
+        let before = plan(
+            "2023",
+            "080901",
+            vec![
+                course("TEST101", Some("第一学年秋季"), None),
+                course("TEST102", Some("第一学年春季"), None),
+            ],
+        );
+        let (temp_dir, repos_dir, docs_dir) = generate_site(
+            "test_update_course_pages_dropped_course",
+            &[before.clone(), other.clone()],
+        )
+        .await;
+        let canonical_path = docs_dir.join("courses/TEST102.mdx");
+        assert!(fs::read_to_string(&canonical_path)
+            .unwrap()
+            .contains("Major 080901"));
+
+        let after = plan(
+            "2023",
+            "080901",
+            vec![course("TEST101", Some("第一学年秋季"), None)],
+        );
+        let update = PageUpdate {
+            plans: BTreeSet::from([("2023".to_string(), "080901".to_string())]),
+            previous: vec![before],
+            ..PageUpdate::default()
+        };
+        let pages = update_course_pages(
+            &[after, other],
+            &update,
+            &repos_dir,
+            &docs_dir,
+            &HashSet::new(),
+            &FileTrees::new(),
+            &Config::default(),
+            None,
+        )
+        .await
+        .unwrap();
+
+        // The dropped course is rewritten without the plan, and its page and
+        // now empty semester folder are gone
+        assert!(pages.written.contains(&canonical_path));
+        assert!(!fs::read_to_string(&canonical_path)
+            .unwrap()
+            .contains("Major 080901"));
+        assert_eq!(
+            pages.removed,
+            vec![
+                docs_dir.join("2023/080901/fresh-spring/TEST102.mdx"),
+                docs_dir.join("2023/080901/fresh-spring"),
+            ]
+        );
+        assert!(docs_dir
+            .join("2023/080902/fresh-autumn/TEST102.mdx")
+            .exists());
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[tokio::test]
+    async fn test_update_course_pages_deleted_readme() {
+        let plans = vec![plan(
+            "2023",
+            "080901",
+            vec![
+                course("TEST101", Some("第一学年秋季"), None),
+                course("TEST102", Some("第一学年秋季"), None),
+            ],
+        )];
+        let (temp_dir, repos_dir, docs_dir) =
+            generate_site("test_update_course_pages_deleted_readme", &plans).await;
+
+        fs::remove_file(repos_dir.join("TEST102.mdx")).unwrap();
+        let update = PageUpdate {
+            courses: BTreeSet::from(["TEST102".to_string()]),
+            ..PageUpdate::default()
+        };
+        let pages = update_course_pages(
+            &plans,
+            &update,
+            &repos_dir,
+            &docs_dir,
+            &HashSet::new(),
+            &FileTrees::new(),
+            &Config::default(),
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            pages.removed,
+            vec![
+                docs_dir.join("courses/TEST102.mdx"),
+                docs_dir.join("2023/080901/fresh-autumn/TEST102.mdx"),
+            ]
+        );
+        assert!(docs_dir
+            .join("2023/080901/fresh-autumn/TEST101.mdx")
+            .exists());
+        let index =
+            fs::read_to_string(docs_dir.join("2023/080901/fresh-autumn/index.mdx")).unwrap();
+        assert!(!index.contains("TEST102"));
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
 }
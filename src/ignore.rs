@@ -0,0 +1,276 @@
+//! Gitignore-style exclusion rules for course files.
+//!
+//! Maintainers hide drafts, solution folders or huge binaries from the generated
+//! file trees, indexes and exports with rules in `.gitignore` syntax. The built-in
+//! rules hiding `README.md` and other repository metadata come first, then global
+//! rules from `[file_tree] exclude` in `fuma.toml`, then the rules of a course from
+//! `repos/{code}.hoaignore` (fetched from the course repository).
+//!
+//! Supported syntax: `#` comments, `!` negation, a trailing `/` for folders only,
+//! a leading or inner `/` to anchor a pattern at the repository root, and the
+//! wildcards `*`, `?`, `[...]` and `**`. The last matching rule wins, and as in git
+//! a file stays excluded when one of its folders is.
+
+use crate::constants::BUILTIN_EXCLUDE_RULES;
+use regex::Regex;
+
+/// Extension of the per-course rules file in the repos directory
+pub const IGNORE_EXTENSION: &str = "hoaignore";
+
+/// A parsed rule
+#[derive(Debug, Clone)]
+struct Rule {
+    regex: Regex,
+    negated: bool,
+    dir_only: bool,
+}
+
+/// Ordered list of exclusion rules
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    rules: Vec<Rule>,
+}
+
+/// Translate a glob into a regex matching a whole path component sequence
+fn glob_regex(glob: &str) -> String {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut regex = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                if chars.get(i + 2) == Some(&'/') {
+                    // `**/` matches zero or more folders
+                    regex.push_str("(?:.*/)?");
+                    i += 3;
+                } else {
+                    regex.push_str(".*");
+                    i += 2;
+                }
+                continue;
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => {
+                // A `]` right after the opening bracket (or its negation) is literal
+                let mut end = i + 1;
+                if matches!(chars.get(end), Some('!' | '^')) {
+                    end += 1;
+                }
+                if chars.get(end) == Some(&']') {
+                    end += 1;
+                }
+                while end < chars.len() && chars[end] != ']' {
+                    end += 1;
+                }
+
+                if end < chars.len() {
+                    regex.push('[');
+                    let mut j = i + 1;
+                    if matches!(chars[j], '!' | '^') {
+                        regex.push('^');
+                        j += 1;
+                    }
+                    for &c in &chars[j..end] {
+                        if matches!(c, '\\' | '[' | ']' | '^' | '&' | '~') {
+                            regex.push('\\');
+                        }
+                        regex.push(c);
+                    }
+                    regex.push(']');
+                    i = end;
+                } else {
+                    regex.push_str(r"\[");
+                }
+            }
+            '\\' if i + 1 < chars.len() => {
+                i += 1;
+                regex.push_str(&regex::escape(&chars[i].to_string()));
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+
+    regex
+}
+
+/// Parse a line of a rules file; `None` for blank lines, comments and invalid globs
+fn parse_rule(line: &str) -> Option<Rule> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let (negated, pattern) = match line.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    let (dir_only, pattern) = match pattern.strip_suffix('/') {
+        Some(rest) => (true, rest),
+        None => (false, pattern),
+    };
+
+    // Patterns with a slash other than a trailing one are relative to the root
+    let anchored = pattern.contains('/');
+    let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+    if pattern.is_empty() {
+        return None;
+    }
+
+    let body = glob_regex(pattern);
+    let regex = if anchored {
+        format!("^{}$", body)
+    } else {
+        format!("^(?:.*/)?{}$", body)
+    };
+
+    match Regex::new(&regex) {
+        Ok(regex) => Some(Rule {
+            regex,
+            negated,
+            dir_only,
+        }),
+        Err(_) => {
+            eprintln!("Warning: Ignoring invalid exclusion rule: {}", line);
+            None
+        }
+    }
+}
+
+impl IgnoreRules {
+    /// Parse rules, one per line
+    pub fn parse<I, S>(lines: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut rules = Self::default();
+        rules.extend(lines);
+        rules
+    }
+
+    /// The built-in rules hiding repository metadata such as `README.md`
+    pub fn builtin() -> Self {
+        Self::parse(BUILTIN_EXCLUDE_RULES)
+    }
+
+    /// Append rules, which take precedence over the existing ones
+    pub fn extend<I, S>(&mut self, lines: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.rules.extend(
+            lines
+                .into_iter()
+                .filter_map(|line| parse_rule(line.as_ref())),
+        );
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether the last rule matching a path excludes it
+    fn matches(&self, path: &str, is_dir: bool) -> bool {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| (is_dir || !rule.dir_only) && rule.regex.is_match(path))
+            .is_some_and(|rule| !rule.negated)
+    }
+
+    /// Whether a file path relative to the repository root is excluded
+    pub fn is_excluded(&self, path: &str) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+
+        // Files of an excluded folder can't be re-included
+        let folders = path.match_indices('/').map(|(index, _)| &path[..index]);
+        for folder in folders {
+            if self.matches(folder, true) {
+                return true;
+            }
+        }
+
+        self.matches(path, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(text: &str) -> IgnoreRules {
+        IgnoreRules::parse(text.lines())
+    }
+
+    #[test]
+    fn test_basename_patterns() {
+        let rules = rules("# drafts\n\n*.zip\ndraft?.pdf\n");
+        assert!(rules.is_excluded("big.zip"));
+        assert!(rules.is_excluded("lab/data/big.zip"));
+        assert!(rules.is_excluded("notes/draft1.pdf"));
+        assert!(!rules.is_excluded("notes/draft10.pdf"));
+        assert!(!rules.is_excluded("zip/a.pdf"));
+        assert!(!rules.is_excluded("# drafts"));
+    }
+
+    #[test]
+    fn test_anchored_and_folder_patterns() {
+        let rules = rules("/solutions/\nexam/*.docx\nanswers/\n");
+        assert!(rules.is_excluded("solutions/hw1.pdf"));
+        assert!(!rules.is_excluded("hw/solutions/hw1.pdf"));
+        assert!(rules.is_excluded("exam/2022.docx"));
+        assert!(!rules.is_excluded("exam/2022/a.docx"));
+        assert!(!rules.is_excluded("old/exam/2022.docx"));
+        // Folder-only patterns match folders at any level, not files
+        assert!(rules.is_excluded("hw/answers/1.pdf"));
+        assert!(!rules.is_excluded("hw/answers"));
+    }
+
+    #[test]
+    fn test_double_star() {
+        let rules = rules("**/tmp/**\nlab/**/*.bin\n");
+        assert!(rules.is_excluded("tmp/a.txt"));
+        assert!(rules.is_excluded("a/b/tmp/c/d.txt"));
+        assert!(rules.is_excluded("lab/x.bin"));
+        assert!(rules.is_excluded("lab/1/2/x.bin"));
+        assert!(!rules.is_excluded("other/x.bin"));
+    }
+
+    #[test]
+    fn test_negation() {
+        let rules = rules("*.pdf\n!keep.pdf\nsecret/\n!secret/open.pdf\n");
+        assert!(rules.is_excluded("a.pdf"));
+        assert!(!rules.is_excluded("docs/keep.pdf"));
+        // An excluded folder can't have files re-included
+        assert!(rules.is_excluded("secret/open.pdf"));
+
+        let mut layered = IgnoreRules::parse(["*.pdf"]);
+        layered.extend(["!*.pdf"]);
+        assert!(!layered.is_excluded("a.pdf"));
+    }
+
+    #[test]
+    fn test_character_classes_and_escapes() {
+        let rules = rules("hw[0-9].pdf\nfile[!a].txt\n\\!important.txt\n\\#hash\nodd[.txt\n");
+        assert!(rules.is_excluded("hw3.pdf"));
+        assert!(!rules.is_excluded("hwa.pdf"));
+        assert!(rules.is_excluded("fileb.txt"));
+        assert!(!rules.is_excluded("filea.txt"));
+        assert!(rules.is_excluded("!important.txt"));
+        assert!(rules.is_excluded("#hash"));
+        assert!(rules.is_excluded("odd[.txt"));
+    }
+
+    #[test]
+    fn test_empty_rules() {
+        let rules = rules("# only comments\n\n");
+        assert!(rules.is_empty());
+        assert!(!rules.is_excluded("a.pdf"));
+    }
+}
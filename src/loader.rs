@@ -5,8 +5,10 @@
 //! upfront, we avoid the N+1 query problem that plagued the Python implementation.
 
 use crate::error::{FumaError, Result};
-use crate::models::{Course, GradeDetail, Plan, TomlPlan};
-use std::collections::{HashMap, HashSet};
+use crate::ignore::{IgnoreRules, IGNORE_EXTENSION};
+use crate::models::{Course, GradeDetail, Plan, RecommendedSemester, TomlPlan, WorktreeData};
+use crate::tree::{FileTree, FileTrees};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 use walkdir::WalkDir;
@@ -133,7 +135,9 @@ pub fn load_all_plans(data_dir: &Path) -> Result<Vec<Plan>> {
                     credit: c.credit,
                     assessment_method: c.assessment_method,
                     course_nature: c.course_nature,
-                    recommended_semester: c.recommended_year_semester,
+                    recommended_semester: c
+                        .recommended_year_semester
+                        .map(RecommendedSemester::joined),
                     hours: c.hours,
                     grade_details,
                 }
@@ -172,6 +176,60 @@ pub fn load_repos_list(repo_root: &Path) -> Result<HashSet<String>> {
         .collect())
 }
 
+/// Load the fetched worktree.json of a course repository, without the files
+/// excluded by the global rules or the course's `.hoaignore`.
+///
+/// Returns None if the course has no worktree data.
+pub fn load_worktree(
+    repos_dir: &Path,
+    code: &str,
+    exclude: &IgnoreRules,
+) -> Result<Option<WorktreeData>> {
+    let path = repos_dir.join(format!("{}.json", code));
+
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&path)?;
+    let mut worktree: WorktreeData = serde_json::from_str(&content)?;
+
+    let ignore_path = repos_dir.join(format!("{}.{}", code, IGNORE_EXTENSION));
+    let mut rules = exclude.clone();
+    if ignore_path.exists() {
+        rules.extend(fs::read_to_string(&ignore_path)?.lines());
+    }
+    if !rules.is_empty() {
+        worktree.0.retain(|path, _| !rules.is_excluded(path));
+    }
+
+    Ok(Some(worktree))
+}
+
+/// Load the file tree of every course of the plans.
+///
+/// Each worktree is read once, so the generators share the result instead of
+/// loading it again. Courses without worktree data are left out.
+pub fn load_file_trees(
+    plans: &[Plan],
+    repos_dir: &Path,
+    exclude: &IgnoreRules,
+) -> Result<FileTrees> {
+    let codes: BTreeSet<&str> = plans
+        .iter()
+        .flat_map(|plan| &plan.courses)
+        .map(|course| course.code.as_str())
+        .collect();
+
+    let mut trees = FileTrees::new();
+    for code in codes {
+        if let Some(worktree) = load_worktree(repos_dir, code, exclude)? {
+            trees.insert(code.to_string(), FileTree::new(worktree, code));
+        }
+    }
+    Ok(trees)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -351,7 +409,7 @@ mod tests {
         writeln!(file, "MATH101").unwrap();
         writeln!(file, "PHYS201").unwrap();
         writeln!(file, "  CHEM301  ").unwrap(); // with whitespace
-        writeln!(file, "").unwrap(); // empty line
+        writeln!(file).unwrap(); // empty line
         writeln!(file, "CS401").unwrap();
 
         let result = load_repos_list(&temp_dir).unwrap();
@@ -421,4 +479,76 @@ mod tests {
 
         let _ = std::fs::remove_dir_all(&temp_dir);
     }
+
+    #[test]
+    fn test_load_worktree_exclusion() {
+        let temp_dir = std::env::temp_dir().join("test_load_worktree_exclusion");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        fs::write(
+            temp_dir.join("MATH1001.json"),
+            r#"{"a.pdf": {"size": 1, "time": null}, "big.zip": {"size": 1, "time": null}, "keep.zip": {"size": 1, "time": null}, "solutions/1.pdf": {"size": 1, "time": null}}"#,
+        )
+        .unwrap();
+        let global = IgnoreRules::parse(["*.zip"]);
+        let paths = |worktree: WorktreeData| {
+            let mut paths: Vec<String> = worktree.0.into_keys().collect();
+            paths.sort();
+            paths
+        };
+
+        let worktree = load_worktree(&temp_dir, "MATH1001", &global)
+            .unwrap()
+            .unwrap();
+        assert_eq!(paths(worktree), vec!["a.pdf", "solutions/1.pdf"]);
+
+        // Course rules come after the global ones
+        fs::write(
+            temp_dir.join("MATH1001.hoaignore"),
+            "solutions/\n!keep.zip\n",
+        )
+        .unwrap();
+        let worktree = load_worktree(&temp_dir, "MATH1001", &global)
+            .unwrap()
+            .unwrap();
+        assert_eq!(paths(worktree), vec!["a.pdf", "keep.zip"]);
+
+        assert!(load_worktree(&temp_dir, "COMP1001", &global)
+            .unwrap()
+            .is_none());
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_load_worktree_builtin_rules() {
+        let temp_dir = std::env::temp_dir().join("test_load_worktree_builtin_rules");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        fs::write(
+            temp_dir.join("MATH1001.json"),
+            r#"{"README.md": {"size": 100, "time": null}, "valid.txt": {"size": 100, "time": null}, ".github/workflow.yml": {"size": 100, "time": null}}"#,
+        )
+        .unwrap();
+        let paths = |worktree: WorktreeData| {
+            let mut paths: Vec<String> = worktree.0.into_keys().collect();
+            paths.sort();
+            paths
+        };
+
+        // Only valid.txt should remain
+        let worktree = load_worktree(&temp_dir, "MATH1001", &IgnoreRules::builtin())
+            .unwrap()
+            .unwrap();
+        assert_eq!(paths(worktree), vec!["valid.txt"]);
+
+        // Built-in rules come first, so a course can re-include its README
+        fs::write(temp_dir.join("MATH1001.hoaignore"), "!README.md\n").unwrap();
+        let worktree = load_worktree(&temp_dir, "MATH1001", &IgnoreRules::builtin())
+            .unwrap()
+            .unwrap();
+        assert_eq!(paths(worktree), vec!["README.md", "valid.txt"]);
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
 }
@@ -3,14 +3,30 @@
 //! This binary replaces the Python-based page generation system with a high-performance
 //! Rust implementation that avoids the N+1 query problem by loading all data upfront.
 
+mod api;
+mod assets;
+mod catalogue;
+mod classify;
+mod compare;
+mod config;
 mod constants;
 mod error;
+mod export;
 mod fetcher;
 mod formatter;
 mod generator;
+mod ignore;
 mod loader;
+mod majors;
 mod models;
+mod natural;
+mod pinyin;
+mod readme;
+mod resources;
+mod search;
+mod serve;
 mod tree;
+mod watch;
 
 use error::Result;
 use std::path::Path;
@@ -21,18 +37,35 @@ use std::{env, fs};
 /// This program:
 /// 1. (Optional) Fetches repos data from GitHub
 /// 2. Loads all training plans from TOML files (avoiding N+1 queries)
-/// 3. Filters courses based on repos_list.txt
-/// 4. Generates course pages with YAML frontmatter
+/// 3. Gives pages to the courses with a README listed in repos_list.txt
+/// 4. Generates course pages with YAML frontmatter (optionally mirroring README images)
 /// 5. Builds file trees from worktree.json data
-/// 6. Formats MDX files for Fumadocs compatibility
+/// 6. Compares and aggregates plans of each major across enrollment years
+/// 7. Formats MDX files for Fumadocs compatibility
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Check for --fetch flag
+    // Check for command line flags
     let args: Vec<String> = env::args().collect();
     let should_fetch = args.contains(&"--fetch".to_string());
+    let should_mirror_assets = args.contains(&"--mirror-assets".to_string());
 
     let repo_root = Path::new(".").to_path_buf();
 
+    // `export` writes the merged dataset without generating pages
+    if args.get(1).map(String::as_str) == Some("export") {
+        return export::run(&repo_root, &args[2..]);
+    }
+
+    // `serve` answers API requests over HTTP until interrupted
+    if args.get(1).map(String::as_str) == Some("serve") {
+        return serve::run(&repo_root, &args[2..]).await;
+    }
+
+    // `watch` rewrites the pages affected by changed inputs until interrupted
+    if args.get(1).map(String::as_str) == Some("watch") {
+        return watch::run(&repo_root, &args[2..]).await;
+    }
+
     println!("Repository root: {}", repo_root.display());
 
     let repos_dir = repo_root.join("repos");
@@ -99,25 +132,21 @@ async fn main() -> Result<()> {
         );
     }
 
+    // Load optional generator configuration
+    let config = config::Config::load(&repo_root)?;
+
     // Load all training plans from TOML files
     let data_dir = repo_root.join("hoa-major-data");
     let plans = loader::load_all_plans(&data_dir)?;
     println!("Loaded {} training plans", plans.len());
 
-    // Filter courses by repos_set (if repos_list.txt exists)
-    let filtered_plans: Vec<_> = if repos_set.is_empty() {
-        plans
-    } else {
-        plans
-            .into_iter()
-            .map(|mut plan| {
-                plan.courses.retain(|c| repos_set.contains(&c.code));
-                plan
-            })
-            .collect()
-    };
-
-    let total_courses: usize = filtered_plans.iter().map(|p| p.courses.len()).sum();
+    // Courses outside repos_set (if repos_list.txt exists) get no pages but still
+    // count towards the plan overviews
+    let total_courses: usize = plans
+        .iter()
+        .flat_map(|p| &p.courses)
+        .filter(|c| repos_set.is_empty() || repos_set.contains(&c.code))
+        .count();
     println!("Total courses to process: {}", total_courses);
 
     // Generate course pages
@@ -127,10 +156,92 @@ async fn main() -> Result<()> {
         fs::create_dir_all(&docs_dir)?;
     }
 
+    // Mirror README images into public/ if --mirror-assets flag is provided
+    let mut asset_mirror = if should_mirror_assets {
+        Some(assets::AssetMirror::load(&repo_root.join("public"))?)
+    } else {
+        None
+    };
+
+    // File trees without the files hidden by the exclusion rules of fuma.toml and
+    // each course's .hoaignore, loaded once for every generator
+    let exclude = config.file_tree.exclude_rules();
+    let trees = loader::load_file_trees(&plans, &repos_dir, &exclude)?;
+
     println!("Generating course pages...");
-    generator::generate_course_pages(&filtered_plans, &repos_dir, &docs_dir, &repos_set).await?;
+    generator::generate_course_pages(
+        &plans,
+        &repos_dir,
+        &docs_dir,
+        &repos_set,
+        &trees,
+        &config,
+        asset_mirror.as_mut(),
+    )
+    .await?;
     println!("Course pages generated successfully");
 
+    // Compare plans of each major across consecutive enrollment years
+    compare::generate_comparisons(
+        &plans,
+        &docs_dir,
+        &repo_root.join("public"),
+        &config.semesters,
+    )?;
+    println!("Plan comparisons generated successfully");
+
+    // Aggregate plans of each major across enrollment years
+    majors::generate_major_pages(&plans, &docs_dir, |code| {
+        generator::has_course_page(code, &repos_dir, &repos_set)
+    })?;
+    println!("Major pages generated successfully");
+
+    // List every course in a global catalogue
+    catalogue::generate_catalogue(
+        &plans,
+        &repos_dir,
+        &repos_set,
+        &trees,
+        &docs_dir,
+        &repo_root.join("public"),
+    )?;
+    println!("Course catalogue generated successfully");
+
+    // Prebuild the client-side search index, one entry per course
+    search::generate_search_index(
+        &plans,
+        &repos_dir,
+        &repos_set,
+        &trees,
+        &repo_root.join("public"),
+    )?;
+    println!("Search index generated successfully");
+
+    // Index the resource files of every course
+    resources::generate_resources_index(
+        &plans,
+        &repos_dir,
+        &repos_set,
+        &trees,
+        &docs_dir,
+        &repo_root.join("public"),
+    )?;
+    println!("Resources index generated successfully");
+
+    // Emit the static JSON API next to the pages
+    api::generate_api(
+        &plans,
+        &repos_dir,
+        &repos_set,
+        &trees,
+        &repo_root.join("public"),
+    )?;
+    println!("Static JSON API generated successfully");
+
+    if let Some(mirror) = &asset_mirror {
+        mirror.save()?;
+    }
+
     // Format MDX files
     println!("Formatting MDX files...");
     let modified_count = formatter::format_all_mdx_files(&docs_dir)?;
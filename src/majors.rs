@@ -0,0 +1,283 @@
+//! Major pages aggregating plans across enrollment years.
+//!
+//! The docs hierarchy is year → major, so this module adds a `majors/` section
+//! with one page per major independent of year: links to every year's plan, the
+//! courses common to all years and the courses that only appear in some years.
+
+use crate::error::Result;
+use crate::generator::{
+    course_page_href, escape_table_cell, format_credit, format_credit_range, keywords_line,
+    yaml_string,
+};
+use crate::models::{latest_course_names, Plan};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// A course of a major with every plan year including it
+struct MajorCourse<'a> {
+    code: &'a str,
+    /// Name in the most recent plan including the course
+    name: &'a str,
+    years: Vec<&'a str>,
+    credits: Vec<Option<f64>>,
+}
+
+/// Course name cell, linked to the canonical page if the course has one
+fn course_link<F: Fn(&str) -> bool>(code: &str, name: &str, has_page: &F) -> String {
+    let name = if name.trim().is_empty() { code } else { name };
+
+    if has_page(code) {
+        format!("[{}]({})", escape_table_cell(name), course_page_href(code))
+    } else {
+        escape_table_cell(name)
+    }
+}
+
+/// Build the page of a major from its plans, ordered by year.
+///
+/// `has_page` tells whether a course code has a canonical course page.
+pub fn build_major_page<F: Fn(&str) -> bool>(plans: &[&Plan], has_page: F) -> String {
+    let Some(latest) = plans.last() else {
+        return String::new();
+    };
+
+    // Collect courses by code, most recent plans first
+    let names = latest_course_names(plans.iter().copied());
+    let mut courses: BTreeMap<&str, MajorCourse> = BTreeMap::new();
+    for plan in plans.iter().rev() {
+        for course in &plan.courses {
+            let entry = courses
+                .entry(course.code.as_str())
+                .or_insert_with(|| MajorCourse {
+                    code: &course.code,
+                    name: names[course.code.as_str()],
+                    years: Vec::new(),
+                    credits: Vec::new(),
+                });
+            if !entry.years.contains(&plan.year.as_str()) {
+                entry.years.push(&plan.year);
+                entry.credits.push(course.credit);
+            }
+        }
+    }
+
+    let summary = format!(
+        "{} 专业共 {} 个年级的培养方案，涉及 {} 门课程",
+        latest.major_code,
+        plans.len(),
+        courses.len()
+    );
+
+    let mut lines = vec![
+        "---".to_string(),
+        format!("title: {}", yaml_string(&latest.major_name)),
+        format!("description: {}", yaml_string(&summary)),
+    ];
+    lines.extend(keywords_line(&latest.major_name));
+    lines.extend([
+        "---".to_string(),
+        "".to_string(),
+        format!("{}。", summary),
+        "".to_string(),
+        "## 各年级培养方案".to_string(),
+        "".to_string(),
+        "<Cards>".to_string(),
+    ]);
+
+    for plan in plans.iter().rev() {
+        let credit: f64 = plan.courses.iter().filter_map(|c| c.credit).sum();
+        lines.push(format!(
+            "  <Card title=\"{} 级\" description=\"{} 门课程 · {} 学分\" href=\"/docs/{}/{}\" />",
+            plan.year,
+            plan.courses.len(),
+            format_credit(Some(credit)),
+            plan.year,
+            plan.major_code
+        ));
+    }
+    lines.push("</Cards>".to_string());
+
+    if let [.., before, after] = plans {
+        lines.push("".to_string());
+        lines.push(format!(
+            "各年级之间的课程变化见[培养方案对比](/docs/compare/{}/{}-{})。",
+            latest.major_code, before.year, after.year
+        ));
+    }
+
+    let (common, partial): (Vec<&MajorCourse>, Vec<&MajorCourse>) =
+        courses.values().partition(|c| c.years.len() == plans.len());
+
+    if !common.is_empty() {
+        lines.extend([
+            "".to_string(),
+            "## 共同课程".to_string(),
+            "".to_string(),
+            format!("所有年级的培养方案均包含以下 {} 门课程。", common.len()),
+            "".to_string(),
+            "| 课程代码 | 课程名称 | 学分 |".to_string(),
+            "| --- | --- | --- |".to_string(),
+        ]);
+        for entry in &common {
+            lines.push(format!(
+                "| {} | {} | {} |",
+                entry.code,
+                course_link(entry.code, entry.name, &has_page),
+                format_credit_range(entry.credits.iter().copied()),
+            ));
+        }
+    }
+
+    if !partial.is_empty() {
+        lines.extend([
+            "".to_string(),
+            "## 部分年级课程".to_string(),
+            "".to_string(),
+            format!(
+                "以下 {} 门课程仅出现在部分年级的培养方案中。",
+                partial.len()
+            ),
+            "".to_string(),
+            "| 课程代码 | 课程名称 | 学分 | 开设年级 |".to_string(),
+            "| --- | --- | --- | --- |".to_string(),
+        ]);
+        for entry in &partial {
+            let mut years = entry.years.clone();
+            years.sort();
+            lines.push(format!(
+                "| {} | {} | {} | {} |",
+                entry.code,
+                course_link(entry.code, entry.name, &has_page),
+                format_credit_range(entry.credits.iter().copied()),
+                years.join("、"),
+            ));
+        }
+    }
+
+    lines.join("\n")
+}
+
+/// Generate the `majors/` section with one page per major.
+pub fn generate_major_pages<F: Fn(&str) -> bool>(
+    plans: &[Plan],
+    docs_dir: &Path,
+    has_page: F,
+) -> Result<()> {
+    let mut by_major: BTreeMap<&str, Vec<&Plan>> = BTreeMap::new();
+    for plan in plans {
+        by_major
+            .entry(plan.major_code.as_str())
+            .or_default()
+            .push(plan);
+    }
+
+    if by_major.is_empty() {
+        return Ok(());
+    }
+
+    let majors_dir = docs_dir.join("majors");
+    fs::create_dir_all(&majors_dir)?;
+
+    let majors_meta = serde_json::json!({
+        "title": "专业",
+        "root": true,
+    });
+    fs::write(
+        majors_dir.join("meta.json"),
+        serde_json::to_string_pretty(&majors_meta)?,
+    )?;
+
+    let mut index = vec![
+        "---".to_string(),
+        "title: 专业".to_string(),
+        "description: 按专业查看各年级的培养方案".to_string(),
+        "---".to_string(),
+        "".to_string(),
+        "<Cards>".to_string(),
+    ];
+
+    for (major_code, mut major_plans) in by_major {
+        major_plans.sort_by(|a, b| a.year.cmp(&b.year));
+        let major_name = major_plans
+            .last()
+            .map(|p| p.major_name.as_str())
+            .unwrap_or(major_code);
+
+        let major_dir = majors_dir.join(major_code);
+        fs::create_dir_all(&major_dir)?;
+        fs::write(
+            major_dir.join("index.mdx"),
+            build_major_page(&major_plans, &has_page),
+        )?;
+
+        let years: Vec<&str> = major_plans.iter().map(|p| p.year.as_str()).collect();
+        index.push(format!(
+            "  <Card title=\"{}\" description=\"{} · {} 级\" href=\"/docs/majors/{}\" />",
+            major_name,
+            major_code,
+            years.join("、"),
+            major_code
+        ));
+    }
+    index.push("</Cards>".to_string());
+
+    fs::write(majors_dir.join("index.mdx"), index.join("\n"))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::course;
+    use crate::models::Course;
+
+    fn plan(year: &str, courses: Vec<Course>) -> Plan {
+        crate::models::test_support::plan(year, "080901", courses)
+            .with_major_name(&format!("计算机 {}", year))
+    }
+
+    #[test]
+    fn test_build_major_page() {
+        let p2022 = plan(
+            "2022",
+            vec![
+                course("MATH1001").with_credit(5.0),
+                course("OLD1001").with_credit(2.0),
+            ],
+        );
+        let p2023 = plan(
+            "2023",
+            vec![
+                course("MATH1001").with_credit(5.5),
+                course("NEW1001").with_credit(3.0),
+            ],
+        );
+
+        let page = build_major_page(&[&p2022, &p2023], |code| code == "MATH1001");
+
+        // The most recent plan names the major and comes first
+        assert!(page.starts_with("---\ntitle: \"计算机 2023\"\n"));
+        assert!(page.contains("\nkeywords: [jisuanji2023, jsj2023]\n---\n"));
+        let recent = page.find("href=\"/docs/2023/080901\"").unwrap();
+        let older = page.find("href=\"/docs/2022/080901\"").unwrap();
+        assert!(recent < older);
+        assert!(page.contains("description=\"2 门课程 · 8.5 学分\""));
+        assert!(page.contains("(/docs/compare/080901/2022-2023)"));
+
+        assert!(page.contains("| MATH1001 | [Course MATH1001](/docs/courses/MATH1001) | 5–5.5 |"));
+        assert!(page.contains("| NEW1001 | Course NEW1001 | 3 | 2023 |"));
+        assert!(page.contains("| OLD1001 | Course OLD1001 | 2 | 2022 |"));
+    }
+
+    #[test]
+    fn test_build_major_page_single_year() {
+        let p2023 = plan("2023", vec![course("MATH1001").with_credit(5.0)]);
+        let page = build_major_page(&[&p2023], |_| false);
+
+        assert!(page.contains("## 共同课程"));
+        assert!(!page.contains("## 部分年级课程"));
+        assert!(!page.contains("/docs/compare/"));
+    }
+}
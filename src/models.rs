@@ -1,3 +1,4 @@
+use crate::classify::{Category, FileType};
 use serde::{Deserialize, Serialize};
 
 // ============================================================================
@@ -26,11 +27,30 @@ pub struct TomlCourse {
     pub credit: Option<f64>,
     pub assessment_method: Option<String>,
     pub course_nature: Option<String>,
-    pub recommended_year_semester: Option<String>,
+    pub recommended_year_semester: Option<RecommendedSemester>,
     pub hours: Option<HourDistribution>,
     pub grade_details: Option<Vec<GradeDetail>>,
 }
 
+/// Recommended semester of a course: a single string, or an array for courses
+/// spanning several semesters
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum RecommendedSemester {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl RecommendedSemester {
+    /// Join the semesters into one delimited string
+    pub fn joined(self) -> String {
+        match self {
+            Self::One(semester) => semester,
+            Self::Many(semesters) => semesters.join("、"),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct GradeDetail {
     pub name: String,
@@ -51,7 +71,7 @@ pub struct HourDistribution {
 // Runtime Data Models
 // ============================================================================
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Plan {
     pub year: String,
     pub major_code: String,
@@ -59,7 +79,7 @@ pub struct Plan {
     pub courses: Vec<Course>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Course {
     pub code: String,
     pub name: String,
@@ -71,6 +91,37 @@ pub struct Course {
     pub grade_details: Option<Vec<GradeDetail>>,
 }
 
+impl Plan {
+    /// Order plans most recent first, then by major code
+    pub fn cmp_recency(&self, other: &Plan) -> std::cmp::Ordering {
+        other
+            .year
+            .cmp(&self.year)
+            .then_with(|| self.major_code.cmp(&other.major_code))
+    }
+}
+
+/// Name of every course in the most recent plan including it.
+///
+/// Plans of the same year are taken in major code order, so the result does not
+/// depend on the order plans were loaded in.
+pub fn latest_course_names<'a, I: IntoIterator<Item = &'a Plan>>(
+    plans: I,
+) -> std::collections::BTreeMap<&'a str, &'a str> {
+    let mut ordered: Vec<&Plan> = plans.into_iter().collect();
+    ordered.sort_by(|a, b| a.cmp_recency(b));
+
+    let mut names = std::collections::BTreeMap::new();
+    for plan in ordered {
+        for course in &plan.courses {
+            names
+                .entry(course.code.as_str())
+                .or_insert(course.name.as_str());
+        }
+    }
+    names
+}
+
 // ============================================================================
 // Worktree JSON Models
 // ============================================================================
@@ -88,17 +139,31 @@ pub struct FileMetadata {
 // File Tree Models
 // ============================================================================
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct FileNode {
     pub name: String,
+    #[serde(rename = "type")]
     pub node_type: NodeType,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub children: Vec<FileNode>,
     pub url: Option<String>,
+    /// Size of a file, or total size of the files in a folder
     pub size: Option<u64>,
+    /// Date of a file, or latest date of the files in a folder
     pub date: Option<String>,
+    /// Number of files in a folder, including subfolders
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_count: Option<usize>,
+    /// Format of a file, from its extension
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_type: Option<FileType>,
+    /// Category of a file, from keywords in its path
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<Category>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum NodeType {
     Folder,
     File,
@@ -112,6 +177,9 @@ pub enum NodeType {
 pub struct Frontmatter {
     pub title: String,
     pub description: String,
+    /// Search keywords, e.g. the pinyin of the title
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
     pub course: CourseMetadata,
 }
 
@@ -141,17 +209,129 @@ pub struct GradingItem {
     pub percent: u32,
 }
 
+/// Frontmatter of a canonical course page.
+///
+/// The canonical page holds the README and file tree shared by every plan, so it
+/// carries no plan-specific course metadata.
+#[derive(Debug, Serialize)]
+pub struct CoursePageFrontmatter {
+    pub title: String,
+    pub description: String,
+    /// Search keywords, e.g. the pinyin of the title
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+}
+
 impl Frontmatter {
     /// Convert frontmatter to YAML string
     pub fn to_yaml(&self) -> String {
-        // Use serde_yaml to serialize, but customize for better formatting
-        match serde_yaml::to_string(self) {
-            Ok(yaml) => format!("---\n{}---", yaml),
-            Err(_) => {
-                // Fallback to empty frontmatter
-                "---\ntitle: ''\ndescription: ''\n---".to_string()
+        to_yaml_block(self, &serde_yaml::Mapping::new())
+    }
+}
+
+impl CoursePageFrontmatter {
+    /// Convert frontmatter to YAML string, appending extra fields (e.g. from README
+    /// frontmatter). Generated fields take precedence over extra fields with the
+    /// same key.
+    pub fn to_yaml_with(&self, extra: &serde_yaml::Mapping) -> String {
+        to_yaml_block(self, extra)
+    }
+}
+
+/// Serialize a frontmatter struct into a `---` delimited YAML block
+fn to_yaml_block<T: Serialize>(frontmatter: &T, extra: &serde_yaml::Mapping) -> String {
+    let value = serde_yaml::to_value(frontmatter).map(|mut value| {
+        if let serde_yaml::Value::Mapping(ref mut mapping) = value {
+            for (key, field) in extra {
+                if !mapping.contains_key(key) {
+                    mapping.insert(key.clone(), field.clone());
+                }
             }
         }
+        value
+    });
+
+    // Use serde_yaml to serialize, but customize for better formatting
+    match value.and_then(|value| serde_yaml::to_string(&value)) {
+        Ok(yaml) => format!("---\n{}---", yaml),
+        Err(_) => {
+            // Fallback to empty frontmatter
+            "---\ntitle: ''\ndescription: ''\n---".to_string()
+        }
+    }
+}
+
+/// Fixtures shared by the tests of the generators
+#[cfg(test)]
+pub mod test_support {
+    use super::{Course, GradeDetail, Plan};
+
+    /// A course named `Course <code>` without any other data
+    pub fn course(code: &str) -> Course {
+        Course {
+            code: code.to_string(),
+            name: format!("Course {}", code),
+            credit: None,
+            assessment_method: None,
+            course_nature: None,
+            recommended_semester: None,
+            hours: None,
+            grade_details: None,
+        }
+    }
+
+    /// A plan of major `Major <major_code>`
+    pub fn plan(year: &str, major_code: &str, courses: Vec<Course>) -> Plan {
+        Plan {
+            year: year.to_string(),
+            major_code: major_code.to_string(),
+            major_name: format!("Major {}", major_code),
+            courses,
+        }
+    }
+
+    impl Course {
+        pub fn with_name(mut self, name: &str) -> Self {
+            self.name = name.to_string();
+            self
+        }
+
+        pub fn with_credit(mut self, credit: f64) -> Self {
+            self.credit = Some(credit);
+            self
+        }
+
+        pub fn with_semester(mut self, semester: &str) -> Self {
+            self.recommended_semester = Some(semester.to_string());
+            self
+        }
+
+        pub fn with_nature(mut self, nature: &str) -> Self {
+            self.course_nature = Some(nature.to_string());
+            self
+        }
+
+        pub fn with_assessment(mut self, method: &str) -> Self {
+            self.assessment_method = Some(method.to_string());
+            self
+        }
+
+        pub fn with_grade(mut self, name: &str, percent: &str) -> Self {
+            self.grade_details
+                .get_or_insert_with(Vec::new)
+                .push(GradeDetail {
+                    name: name.to_string(),
+                    percent: Some(percent.to_string()),
+                });
+            self
+        }
+    }
+
+    impl Plan {
+        pub fn with_major_name(mut self, major_name: &str) -> Self {
+            self.major_name = major_name.to_string();
+            self
+        }
     }
 }
 
@@ -159,11 +339,44 @@ impl Frontmatter {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_latest_course_names() {
+        use test_support::{course, plan};
+
+        let plans = vec![
+            plan(
+                "2022",
+                "080901",
+                vec![course("MATH1001").with_name("数学分析")],
+            ),
+            plan(
+                "2023",
+                "080902",
+                vec![
+                    course("MATH1001").with_name("高等数学 B"),
+                    course("COMP1001").with_name("程序设计"),
+                ],
+            ),
+            plan(
+                "2023",
+                "080901",
+                vec![course("MATH1001").with_name("高等数学")],
+            ),
+        ];
+        let names = latest_course_names(&plans);
+        assert_eq!(names["MATH1001"], "高等数学");
+        assert_eq!(names["COMP1001"], "程序设计");
+
+        let reversed: Vec<&Plan> = plans.iter().rev().collect();
+        assert_eq!(latest_course_names(reversed), names);
+    }
+
     #[test]
     fn test_frontmatter_to_yaml_basic() {
         let frontmatter = Frontmatter {
             title: "Test Course".to_string(),
             description: "A test description".to_string(),
+            keywords: Vec::new(),
             course: CourseMetadata {
                 credit: 3,
                 assessment_method: "Exam".to_string(),
@@ -205,6 +418,7 @@ mod tests {
         let frontmatter = Frontmatter {
             title: "Advanced Math".to_string(),
             description: "".to_string(),
+            keywords: Vec::new(),
             course: CourseMetadata {
                 credit: 4,
                 assessment_method: "Mixed".to_string(),
@@ -250,6 +464,7 @@ mod tests {
         let frontmatter = Frontmatter {
             title: "Simple Course".to_string(),
             description: "No grading details".to_string(),
+            keywords: Vec::new(),
             course: CourseMetadata {
                 credit: 2,
                 assessment_method: "Pass/Fail".to_string(),
@@ -277,6 +492,7 @@ mod tests {
         let frontmatter = Frontmatter {
             title: "Complex Course".to_string(),
             description: "".to_string(),
+            keywords: Vec::new(),
             course: CourseMetadata {
                 credit: 5,
                 assessment_method: "Comprehensive".to_string(),
@@ -303,6 +519,39 @@ mod tests {
         assert!(yaml.contains("tutoring: 2"));
     }
 
+    #[test]
+    fn test_course_page_frontmatter_with_extra_fields() {
+        let frontmatter = CoursePageFrontmatter {
+            title: "Course".to_string(),
+            description: "".to_string(),
+            keywords: Vec::new(),
+        };
+
+        let extra: serde_yaml::Mapping =
+            serde_yaml::from_str("title: Overridden\ntags:\n- math\n").unwrap();
+        let yaml = frontmatter.to_yaml_with(&extra);
+
+        assert!(yaml.starts_with("---\ntitle: Course\n"));
+        assert!(!yaml.contains("Overridden"));
+        assert!(yaml.contains("tags:\n- math"));
+        assert!(yaml.ends_with("---"));
+    }
+
+    #[test]
+    fn test_recommended_semester_string_or_array() {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            semester: RecommendedSemester,
+        }
+
+        let one: Wrapper = toml::from_str("semester = \"第一学年秋季\"").unwrap();
+        assert_eq!(one.semester.joined(), "第一学年秋季");
+
+        let many: Wrapper =
+            toml::from_str("semester = [\"第一学年秋季\", \"第一学年春季\"]").unwrap();
+        assert_eq!(many.semester.joined(), "第一学年秋季、第一学年春季");
+    }
+
     #[test]
     fn test_grading_item_serialization() {
         let item = GradingItem {
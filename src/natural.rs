@@ -0,0 +1,174 @@
+//! Natural ordering of file names.
+//!
+//! Names are compared as sequences of text and numbers, so `lecture2.pdf` comes
+//! before `lecture10.pdf` and `第二章` before `第十章`. Both Arabic digits and
+//! Chinese numerals (一…九, 十, 百, 千, 零/〇, 两) count as numbers; text is
+//! compared case-insensitively.
+
+use std::cmp::Ordering;
+
+/// A piece of a name
+#[derive(Debug, PartialEq)]
+enum Chunk {
+    Number(u64),
+    Char(char),
+}
+
+fn chinese_digit(c: char) -> Option<u64> {
+    Some(match c {
+        '零' | '〇' => 0,
+        '一' => 1,
+        '二' | '两' => 2,
+        '三' => 3,
+        '四' => 4,
+        '五' => 5,
+        '六' => 6,
+        '七' => 7,
+        '八' => 8,
+        '九' => 9,
+        _ => return None,
+    })
+}
+
+fn chinese_unit(c: char) -> Option<u64> {
+    Some(match c {
+        '十' => 10,
+        '百' => 100,
+        '千' => 1000,
+        _ => return None,
+    })
+}
+
+/// Value of a run of Chinese numerals, e.g. 23 for 二十三 and 105 for 一百零五.
+///
+/// A run without 十/百/千 is read digit by digit, so 二〇二二 is 2022.
+fn chinese_value(run: &[char]) -> u64 {
+    if !run.iter().any(|&c| chinese_unit(c).is_some()) {
+        return run
+            .iter()
+            .filter_map(|&c| chinese_digit(c))
+            .fold(0u64, |value, d| value.saturating_mul(10).saturating_add(d));
+    }
+
+    let mut total = 0;
+    let mut digit = 0;
+    for &c in run {
+        if let Some(d) = chinese_digit(c) {
+            digit = d;
+        } else if let Some(unit) = chinese_unit(c) {
+            // A leading 十 means one ten
+            total += digit.max(1) * unit;
+            digit = 0;
+        }
+    }
+    total + digit
+}
+
+/// Split a name into numbers and lowercased characters
+fn chunks(name: &str) -> Vec<Chunk> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut chunks = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            let digits: String = chars[start..i].iter().collect();
+            chunks.push(Chunk::Number(digits.parse().unwrap_or(u64::MAX)));
+        } else if chinese_digit(c).is_some() || chinese_unit(c).is_some() {
+            let start = i;
+            while i < chars.len()
+                && (chinese_digit(chars[i]).is_some() || chinese_unit(chars[i]).is_some())
+            {
+                i += 1;
+            }
+            chunks.push(Chunk::Number(chinese_value(&chars[start..i])));
+        } else {
+            chunks.extend(c.to_lowercase().map(Chunk::Char));
+            i += 1;
+        }
+    }
+
+    chunks
+}
+
+/// Compare two names in natural order.
+///
+/// Numbers sort before text at the same position. Names that only differ in how
+/// their numbers are written (`2` and `02`) fall back to plain comparison.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (chunks_a, chunks_b) = (chunks(a), chunks(b));
+
+    for (x, y) in chunks_a.iter().zip(&chunks_b) {
+        let ordering = match (x, y) {
+            (Chunk::Number(x), Chunk::Number(y)) => x.cmp(y),
+            (Chunk::Number(_), Chunk::Char(_)) => Ordering::Less,
+            (Chunk::Char(_), Chunk::Number(_)) => Ordering::Greater,
+            (Chunk::Char(x), Chunk::Char(y)) => x.cmp(y),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    chunks_a.len().cmp(&chunks_b.len()).then_with(|| a.cmp(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(names: &[&str]) -> Vec<String> {
+        let mut names: Vec<String> = names.iter().map(|s| s.to_string()).collect();
+        names.sort_by(|a, b| natural_cmp(a, b));
+        names
+    }
+
+    #[test]
+    fn test_chinese_value() {
+        let value = |s: &str| chinese_value(&s.chars().collect::<Vec<_>>());
+        assert_eq!(value("三"), 3);
+        assert_eq!(value("十"), 10);
+        assert_eq!(value("十二"), 12);
+        assert_eq!(value("二十"), 20);
+        assert_eq!(value("二十三"), 23);
+        assert_eq!(value("一百零五"), 105);
+        assert_eq!(value("两千"), 2000);
+        assert_eq!(value("二〇二二"), 2022);
+        assert_eq!(value("二〇二三"), 2023);
+    }
+
+    #[test]
+    fn test_natural_cmp_numbers() {
+        assert_eq!(
+            sorted(&["lecture10.pdf", "lecture2.pdf", "Lecture1.pdf"]),
+            vec!["Lecture1.pdf", "lecture2.pdf", "lecture10.pdf"]
+        );
+        assert_eq!(
+            sorted(&["hw10", "hw9", "hw", "hw09"]),
+            vec!["hw", "hw09", "hw9", "hw10"]
+        );
+    }
+
+    #[test]
+    fn test_natural_cmp_chinese_numerals() {
+        assert_eq!(
+            sorted(&["第十章", "第二章", "第十一章", "第一章", "第3章"]),
+            vec!["第一章", "第二章", "第3章", "第十章", "第十一章"]
+        );
+        assert_eq!(
+            sorted(&["二〇二三年试卷", "二〇二二年试卷"]),
+            vec!["二〇二二年试卷", "二〇二三年试卷"]
+        );
+    }
+
+    #[test]
+    fn test_natural_cmp_case_insensitive() {
+        assert_eq!(natural_cmp("B.pdf", "a.pdf"), Ordering::Greater);
+        assert_eq!(natural_cmp("a", "a"), Ordering::Equal);
+    }
+}
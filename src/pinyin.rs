@@ -0,0 +1,128 @@
+//! Toneless pinyin of Chinese text for search.
+//!
+//! Each Han character maps to a single reading from `pinyin_table.txt`, which is
+//! enough to match course and major names typed as full pinyin (`gaodengshuxue`)
+//! or initials (`gdsx`). Letters and digits are kept lowercased; other characters
+//! are dropped. Pages carry both forms as frontmatter `keywords` for site search.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Syllable followed by its characters on each line
+const PINYIN_TABLE: &str = include_str!("pinyin_table.txt");
+
+fn table() -> &'static HashMap<char, &'static str> {
+    static TABLE: OnceLock<HashMap<char, &'static str>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        PINYIN_TABLE
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| line.split_once(' '))
+            .flat_map(|(syllable, chars)| chars.chars().map(move |c| (c, syllable)))
+            .collect()
+    })
+}
+
+/// A searchable unit of text
+enum Unit {
+    /// Pinyin syllable of a Han character
+    Syllable(&'static str),
+    /// Run of letters and digits, lowercased
+    Word(String),
+}
+
+/// Split text into pinyin syllables and runs of letters and digits
+fn units(text: &str) -> Vec<Unit> {
+    let mut units = Vec::new();
+    let mut word = String::new();
+
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() {
+            word.push(c.to_ascii_lowercase());
+            continue;
+        }
+        if !word.is_empty() {
+            units.push(Unit::Word(std::mem::take(&mut word)));
+        }
+        if let Some(syllable) = table().get(&c) {
+            units.push(Unit::Syllable(syllable));
+        }
+    }
+    if !word.is_empty() {
+        units.push(Unit::Word(word));
+    }
+
+    units
+}
+
+/// Full pinyin spelling, e.g. `gaodengshuxue` for 高等数学
+pub fn full(text: &str) -> String {
+    units(text)
+        .iter()
+        .map(|unit| match unit {
+            Unit::Syllable(syllable) => syllable,
+            Unit::Word(word) => word.as_str(),
+        })
+        .collect()
+}
+
+/// Pinyin initials, e.g. `gdsx` for 高等数学; letter and digit runs are kept whole
+pub fn initials(text: &str) -> String {
+    units(text)
+        .iter()
+        .map(|unit| match unit {
+            Unit::Syllable(syllable) => &syllable[..1],
+            Unit::Word(word) => word.as_str(),
+        })
+        .collect()
+}
+
+/// Search keywords of a name: full pinyin and initials, e.g. `gaodengshuxue` and
+/// `gdsx` for 高等数学. Names without Han characters get none.
+pub fn keywords(name: &str) -> Vec<String> {
+    if !units(name)
+        .iter()
+        .any(|unit| matches!(unit, Unit::Syllable(_)))
+    {
+        return Vec::new();
+    }
+
+    let mut keywords = vec![full(name)];
+    let initials = initials(name);
+    if !keywords.contains(&initials) {
+        keywords.push(initials);
+    }
+    keywords
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full() {
+        assert_eq!(full("高等数学"), "gaodengshuxue");
+        assert_eq!(full("数据结构"), "shujujiegou");
+        assert_eq!(full("大学物理A（上）"), "daxuewuliashang");
+        assert_eq!(full("C++程序设计"), "cchengxusheji");
+        assert_eq!(full("长度"), "changdu");
+        assert_eq!(full(""), "");
+    }
+
+    #[test]
+    fn test_initials() {
+        assert_eq!(initials("高等数学"), "gdsx");
+        assert_eq!(initials("计算机科学与技术"), "jsjkxyjs");
+        assert_eq!(initials("大学物理2"), "dxwl2");
+        assert_eq!(initials("MATLAB 编程"), "matlabbc");
+    }
+
+    #[test]
+    fn test_keywords() {
+        assert_eq!(keywords("高等数学"), vec!["gaodengshuxue", "gdsx"]);
+        assert_eq!(keywords("数学"), vec!["shuxue", "sx"]);
+        assert_eq!(keywords("体"), vec!["ti", "t"]);
+        assert!(keywords("MATLAB").is_empty());
+        assert!(keywords("").is_empty());
+    }
+}
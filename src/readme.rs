@@ -0,0 +1,702 @@
+//! Course README processing.
+//!
+//! READMEs are written for GitHub: they start with the course title and resolve
+//! relative links against the course repository. Before the content is copied into
+//! `content/docs/...`, the title (and an optional YAML frontmatter block) is split
+//! off and relative links are rewritten to absolute URLs.
+
+use crate::tree::generate_download_url;
+use regex::{Captures, Regex};
+use serde_yaml::Mapping;
+use std::sync::LazyLock;
+
+/// Fenced code blocks (backtick or tilde) and inline code spans, whose links stay
+/// verbatim
+static CODE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"```[\s\S]*?```|~~~[\s\S]*?~~~|``[^\n]*?``|`[^`\n]+`").unwrap());
+
+/// Markdown inline links and images: `[text](target "title")` / `![alt](target)`
+static INLINE_LINK_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(\]\(\s*)(<[^>\n]*>|[^\s)]+)").unwrap());
+
+/// Reference definitions: `[id]: target`
+static REFERENCE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^(\s{0,3}\[[^\]\n]+\]:[ \t]*)(<[^>\n]*>|\S+)").unwrap());
+
+/// Inline HTML attributes: `<img src="...">`, `<a href='...'>`
+static HTML_DOUBLE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(<[a-zA-Z][^>]*?\s(?:href|src)=")([^"]*)(")"#).unwrap());
+static HTML_SINGLE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(<[a-zA-Z][^>]*?\s(?:href|src)=')([^']*)(')").unwrap());
+
+/// URL scheme of an absolute link, e.g. `https:` or `mailto:`
+static SCHEME_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[a-zA-Z][a-zA-Z0-9+.-]*:").unwrap());
+
+/// Link to the root of a course repository, capturing the repository name
+static REPO_URL_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^https?://(?:www\.)?github\.com/HITSZ-OpenAuto/([^/?#]+)/?(?:#.*)?$").unwrap()
+});
+
+/// ATX H1 heading, capturing its text without a closing sequence
+static ATX_H1_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^ {0,3}#[ \t]+(.*?)(?:[ \t]+#+)?[ \t]*$").unwrap());
+/// Underline of a setext H1 heading
+static SETEXT_H1_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^ {0,3}=+[ \t]*$").unwrap());
+
+/// HTML comment
+static COMMENT_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<!--[\s\S]*?-->").unwrap());
+/// List item marker at the start of a line
+static LIST_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(?:[-*+]|\d+[.)])\s").unwrap());
+/// Thematic break
+static RULE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(?:[-*_]\s*){3,}$").unwrap());
+/// ATX heading of any level, capturing its text
+static HEADING_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^#{1,6}\s+(.*?)(?:\s+#+)?\s*$").unwrap());
+
+/// Inline syntax removed by [`strip_markdown`]
+static IMAGE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"!\[[^\]]*\]\([^)]*\)").unwrap());
+static LINK_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[([^\]]*)\](?:\([^)]*\)|\[[^\]]*\])").unwrap());
+static HTML_TAG_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"</?[a-zA-Z][^>]*>").unwrap());
+static EMPHASIS_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\*\*|__|~~|\*|`").unwrap());
+static SPACE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\s+").unwrap());
+
+/// A course README split into its parts
+#[derive(Debug, Default)]
+pub struct ReadmeParts {
+    /// Fields of a leading YAML frontmatter block
+    pub frontmatter: Mapping,
+    /// Text of the leading H1 heading, if any
+    pub title: Option<String>,
+    /// Content after the frontmatter and title
+    pub body: String,
+}
+
+/// Split a README into frontmatter, leading H1 title and body.
+///
+/// Only a leading H1 (ATX `# Title` or setext `Title` + `===`) is stripped, so
+/// READMEs starting with a badge line or plain text keep all their content. A
+/// byte order mark is ignored, and a frontmatter block that is not a valid YAML
+/// mapping is left in the body.
+pub fn split_readme(content: &str) -> ReadmeParts {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let lines: Vec<&str> = content.lines().collect();
+    let mut parts = ReadmeParts::default();
+    let mut start = 0;
+
+    // YAML frontmatter delimited by `---`
+    if lines.first().is_some_and(|l| l.trim_end() == "---") {
+        if let Some(end) = lines
+            .iter()
+            .skip(1)
+            .position(|l| matches!(l.trim_end(), "---" | "..."))
+        {
+            let yaml = lines[1..=end].join("\n");
+            if let Ok(mapping) = serde_yaml::from_str::<Mapping>(&yaml) {
+                parts.frontmatter = mapping;
+                start = end + 2;
+            }
+        }
+    }
+
+    let skip_blank = |mut i: usize| {
+        while i < lines.len() && lines[i].trim().is_empty() {
+            i += 1;
+        }
+        i
+    };
+    start = skip_blank(start);
+
+    // Leading H1 heading
+    if let Some(line) = lines.get(start) {
+        if let Some(caps) = ATX_H1_RE.captures(line) {
+            parts.title = Some(caps[1].trim().to_string());
+            start = skip_blank(start + 1);
+        } else if lines
+            .get(start + 1)
+            .is_some_and(|l| SETEXT_H1_RE.is_match(l))
+        {
+            parts.title = Some(line.trim().to_string());
+            start = skip_blank(start + 2);
+        }
+    }
+
+    parts.body = lines[start.min(lines.len())..].join("\n");
+    parts
+}
+
+/// Maximum display width of a generated description (CJK characters count double)
+const DESCRIPTION_MAX_WIDTH: usize = 160;
+
+/// Derive a page description from the first meaningful paragraph of README content.
+///
+/// Headings, tables, lists, code, math and HTML-only blocks are skipped. Markdown
+/// syntax is stripped, and long text is truncated at a sentence boundary where
+/// possible, counting CJK characters as double width.
+pub fn extract_description(content: &str) -> Option<String> {
+    let content = COMMENT_RE.replace_all(content, "");
+
+    let mut paragraphs: Vec<Vec<&str>> = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    let mut in_code = false;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+            if !current.is_empty() {
+                paragraphs.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        if trimmed.is_empty() {
+            if !current.is_empty() {
+                paragraphs.push(std::mem::take(&mut current));
+            }
+        } else if trimmed.starts_with('#') {
+            // Headings interrupt paragraphs
+            if !current.is_empty() {
+                paragraphs.push(std::mem::take(&mut current));
+            }
+            paragraphs.push(vec![trimmed]);
+        } else {
+            current.push(trimmed);
+        }
+    }
+    if !current.is_empty() {
+        paragraphs.push(current);
+    }
+
+    paragraphs
+        .into_iter()
+        .filter(|lines| {
+            let first = lines[0];
+            !(first.starts_with('#')
+                || first.starts_with('|')
+                || first.starts_with('<')
+                || first.starts_with("{{")
+                || first.starts_with("$$")
+                || first.starts_with("[//]")
+                || LIST_RE.is_match(first)
+                || RULE_RE.is_match(first))
+        })
+        .map(|lines| {
+            let lines: Vec<&str> = lines
+                .iter()
+                .map(|l| l.trim_start_matches('>').trim())
+                .collect();
+            strip_markdown(&join_lines(&lines))
+        })
+        .find(|text| text.chars().any(char::is_alphanumeric))
+        .map(|text| truncate_description(&text, DESCRIPTION_MAX_WIDTH))
+}
+
+/// Extract the text of the ATX headings of README content, skipping code blocks.
+///
+/// Inline Markdown is stripped and closing `#` sequences removed; empty headings
+/// are skipped.
+pub fn extract_headings(content: &str) -> Vec<String> {
+    let mut headings = Vec::new();
+    let mut in_code = false;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        if let Some(caps) = HEADING_RE.captures(trimmed) {
+            let text = strip_markdown(&caps[1]);
+            if !text.is_empty() {
+                headings.push(text);
+            }
+        }
+    }
+
+    headings
+}
+
+/// Check whether a character is rendered double width (CJK and fullwidth forms)
+fn is_wide(c: char) -> bool {
+    matches!(c as u32,
+        0x1100..=0x115F
+        | 0x2E80..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x20000..=0x3FFFD)
+}
+
+/// Join paragraph lines, omitting the space between two CJK characters
+fn join_lines(lines: &[&str]) -> String {
+    let mut result = String::new();
+    for line in lines.iter().filter(|l| !l.is_empty()) {
+        let needs_space = match (result.chars().last(), line.chars().next()) {
+            (Some(a), Some(b)) => !(is_wide(a) && is_wide(b)),
+            _ => false,
+        };
+        if needs_space {
+            result.push(' ');
+        }
+        result.push_str(line);
+    }
+    result
+}
+
+/// Strip inline Markdown and HTML syntax, keeping the visible text
+fn strip_markdown(text: &str) -> String {
+    let text = IMAGE_RE.replace_all(text, "");
+    let text = LINK_RE.replace_all(&text, "$1");
+    let text = HTML_TAG_RE.replace_all(&text, "");
+    let text = EMPHASIS_RE.replace_all(&text, "");
+    SPACE_RE.replace_all(&text, " ").trim().to_string()
+}
+
+/// Truncate text to a display width, preferring to cut after a sentence end
+fn truncate_description(text: &str, max_width: usize) -> String {
+    let width = |s: &str| {
+        s.chars()
+            .map(|c| if is_wide(c) { 2 } else { 1 })
+            .sum::<usize>()
+    };
+    if width(text) <= max_width {
+        return text.to_string();
+    }
+
+    // Longest prefix that fits, leaving room for the ellipsis
+    let mut end = 0;
+    let mut used = 0;
+    for (i, c) in text.char_indices() {
+        let w = if is_wide(c) { 2 } else { 1 };
+        if used + w > max_width - 1 {
+            break;
+        }
+        used += w;
+        end = i + c.len_utf8();
+    }
+    let prefix = &text[..end];
+
+    // Cut after the last sentence end if it keeps at least half of the text
+    let sentence_end = prefix
+        .char_indices()
+        .rev()
+        .filter(|&(_, c)| matches!(c, '。' | '！' | '？' | '；' | '.' | '!' | '?' | ';'))
+        .map(|(i, c)| i + c.len_utf8())
+        .find(|&i| width(&prefix[..i]) >= max_width / 2);
+    if let Some(i) = sentence_end {
+        return prefix[..i].to_string();
+    }
+
+    // Otherwise avoid splitting a Latin word
+    let cut = match prefix.rfind(' ') {
+        Some(i)
+            if !prefix.ends_with(|c: char| is_wide(c)) && width(&prefix[..i]) >= max_width / 2 =>
+        {
+            &prefix[..i]
+        }
+        _ => prefix,
+    };
+    format!("{}…", cut.trim_end())
+}
+
+/// Rewrite relative links and image sources in README content to absolute URLs.
+///
+/// Handles Markdown inline links and images, reference definitions, and `href`/`src`
+/// attributes of inline HTML. Relative paths are resolved against the root of `repo`:
+/// files point at the download mirror, directories at the GitHub tree view. Links to
+/// other course repositories (`https://github.com/HITSZ-OpenAuto/<CODE>` or
+/// `../<CODE>`) are replaced with the doc page returned by `course_page`.
+///
+/// Fenced code blocks and inline code spans are left untouched.
+pub fn rewrite_links<F>(content: &str, repo: &str, course_page: F) -> String
+where
+    F: Fn(&str) -> Option<String>,
+{
+    // Protect code so that links in code samples stay verbatim
+    let mut code_blocks = Vec::new();
+    let protected_content = CODE_RE.replace_all(content, |caps: &Captures| {
+        code_blocks.push(caps[0].to_string());
+        format!("___CODE_BLOCK_PLACEHOLDER_{}___", code_blocks.len() - 1)
+    });
+
+    let rewrite = |prefix: &str, target: &str, suffix: &str| -> String {
+        let resolved = resolve_target(target, repo, &course_page);
+        format!(
+            "{}{}{}",
+            prefix,
+            resolved.as_deref().unwrap_or(target),
+            suffix
+        )
+    };
+
+    let mut result = INLINE_LINK_RE
+        .replace_all(&protected_content, |caps: &Captures| {
+            rewrite(&caps[1], &caps[2], "")
+        })
+        .to_string();
+
+    result = REFERENCE_RE
+        .replace_all(&result, |caps: &Captures| rewrite(&caps[1], &caps[2], ""))
+        .to_string();
+
+    result = HTML_DOUBLE_RE
+        .replace_all(&result, |caps: &Captures| {
+            rewrite(&caps[1], &caps[2], &caps[3])
+        })
+        .to_string();
+
+    result = HTML_SINGLE_RE
+        .replace_all(&result, |caps: &Captures| {
+            rewrite(&caps[1], &caps[2], &caps[3])
+        })
+        .to_string();
+
+    // Restore code blocks
+    for (i, block) in code_blocks.iter().enumerate() {
+        let placeholder = format!("___CODE_BLOCK_PLACEHOLDER_{}___", i);
+        result = result.replace(&placeholder, block);
+    }
+
+    result
+}
+
+/// Resolve a single link target, returning None if it should be left unchanged
+fn resolve_target<F>(target: &str, repo: &str, course_page: &F) -> Option<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let target = target.trim_start_matches('<').trim_end_matches('>').trim();
+
+    if target.is_empty() || target.starts_with('#') {
+        return None;
+    }
+
+    // Absolute URLs: only links to other course repositories are rewritten
+    if SCHEME_RE.is_match(target) || target.starts_with("//") {
+        return REPO_URL_RE
+            .captures(target)
+            .and_then(|caps| course_page(&caps[1]));
+    }
+
+    // Split off fragment and query, which are kept as-is
+    let (path, fragment) = match target.find('#') {
+        Some(pos) => target.split_at(pos),
+        None => (target, ""),
+    };
+    let (path, query) = match path.find('?') {
+        Some(pos) => path.split_at(pos),
+        None => (path, ""),
+    };
+
+    // Normalize the path against the repository root (GitHub treats a leading `/`
+    // as the repository root as well)
+    let is_directory = path.ends_with('/');
+    let mut segments: Vec<String> = Vec::new();
+    let mut escaped = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                if segments.pop().is_none() {
+                    escaped.push(segment);
+                }
+            }
+            _ => {
+                let decoded = urlencoding::decode(segment)
+                    .map(|s| s.into_owned())
+                    .unwrap_or_else(|_| segment.to_string());
+                if escaped.is_empty() {
+                    segments.push(decoded);
+                } else {
+                    escaped.push(segment);
+                }
+            }
+        }
+    }
+
+    // `../<CODE>` points at a sibling course repository
+    if !escaped.is_empty() {
+        return match escaped.as_slice() {
+            ["..", code] => course_page(code),
+            _ => None,
+        };
+    }
+
+    let path = segments.join("/");
+    let url = if is_directory || path.is_empty() {
+        let encoded: Vec<String> = segments
+            .iter()
+            .map(|s| urlencoding::encode(s).into_owned())
+            .collect();
+        format!(
+            "https://github.com/HITSZ-OpenAuto/{}/tree/main/{}",
+            repo,
+            encoded.join("/")
+        )
+    } else {
+        generate_download_url(repo, &path)
+    };
+
+    Some(format!("{}{}{}", url, query, fragment))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_readme_atx_title() {
+        let parts = split_readme("# 高等数学\n\nContent line\n\nMore");
+        assert_eq!(parts.title.as_deref(), Some("高等数学"));
+        assert_eq!(parts.body, "Content line\n\nMore");
+        assert!(parts.frontmatter.is_empty());
+    }
+
+    #[test]
+    fn test_split_readme_title_without_blank_line() {
+        let parts = split_readme("# Title #\nContent");
+        assert_eq!(parts.title.as_deref(), Some("Title"));
+        assert_eq!(parts.body, "Content");
+    }
+
+    #[test]
+    fn test_split_readme_setext_title() {
+        let parts = split_readme("Title\n=====\n\nContent");
+        assert_eq!(parts.title.as_deref(), Some("Title"));
+        assert_eq!(parts.body, "Content");
+    }
+
+    #[test]
+    fn test_split_readme_keeps_content_without_title() {
+        let parts = split_readme("First paragraph.\n\nSecond paragraph.");
+        assert_eq!(parts.title, None);
+        assert_eq!(parts.body, "First paragraph.\n\nSecond paragraph.");
+
+        let parts = split_readme("![badge](https://img.shields.io/x)\n# Title\nContent");
+        assert_eq!(parts.title, None);
+        assert!(parts.body.starts_with("![badge]"));
+
+        let parts = split_readme("## Section\nContent");
+        assert_eq!(parts.title, None);
+        assert_eq!(parts.body, "## Section\nContent");
+    }
+
+    #[test]
+    fn test_split_readme_bom_and_frontmatter() {
+        let parts =
+            split_readme("\u{feff}---\ntitle: Custom\ntags: [a, b]\n---\n\n# Heading\n\nBody");
+        assert_eq!(parts.title.as_deref(), Some("Heading"));
+        assert_eq!(parts.body, "Body");
+        assert_eq!(
+            parts.frontmatter.get("title").and_then(|v| v.as_str()),
+            Some("Custom")
+        );
+        assert!(parts.frontmatter.contains_key("tags"));
+    }
+
+    #[test]
+    fn test_split_readme_invalid_frontmatter_is_content() {
+        let parts = split_readme("---\nnot: [valid\n---\nBody");
+        assert!(parts.frontmatter.is_empty());
+        assert_eq!(parts.body, "---\nnot: [valid\n---\nBody");
+    }
+
+    #[test]
+    fn test_extract_description_first_paragraph() {
+        let content = "![cover](a.png)\n\n## 简介\n\n本课程介绍**数据结构**与[算法](https://example.com)。\n\n第二段。";
+        assert_eq!(
+            extract_description(content).as_deref(),
+            Some("本课程介绍数据结构与算法。")
+        );
+    }
+
+    #[test]
+    fn test_extract_description_skips_non_paragraphs() {
+        let content = "<!-- note -->\n| a | b |\n|---|---|\n\n- item\n- item\n\n```\ncode\n```\n\n<div align=\"center\">x</div>\n\n> Course notes and `past exams`.";
+        assert_eq!(
+            extract_description(content).as_deref(),
+            Some("Course notes and past exams.")
+        );
+    }
+
+    #[test]
+    fn test_extract_description_joins_lines() {
+        assert_eq!(
+            extract_description("第一行\n第二行\nand English\ntext").as_deref(),
+            Some("第一行第二行 and English text")
+        );
+    }
+
+    #[test]
+    fn test_extract_description_heading_interrupts_paragraph() {
+        let content = "[![badge](https://img.shields.io/x)](x)\n# Title\n\nBody text.";
+        assert_eq!(extract_description(content).as_deref(), Some("Body text."));
+    }
+
+    #[test]
+    fn test_extract_description_none() {
+        assert_eq!(extract_description(""), None);
+        assert_eq!(extract_description("# Only heading\n\n![img](a.png)"), None);
+    }
+
+    #[test]
+    fn test_extract_headings() {
+        let content = "## 课程简介\n\ntext\n\n### [往年试卷](exam/) ##\n\n```\n# not a heading\n```\n\n#hashtag\n\n## \n";
+        assert_eq!(extract_headings(content), vec!["课程简介", "往年试卷"]);
+    }
+
+    #[test]
+    fn test_truncate_description_cjk_sentence() {
+        let text = format!("{}。{}", "甲".repeat(50), "乙".repeat(50));
+        let output = truncate_description(&text, 160);
+        assert_eq!(output, format!("{}。", "甲".repeat(50)));
+    }
+
+    #[test]
+    fn test_truncate_description_hard_cut() {
+        let text = "字".repeat(100);
+        let output = truncate_description(&text, 160);
+        assert_eq!(output, format!("{}…", "字".repeat(79)));
+
+        let text = "word ".repeat(50);
+        let output = truncate_description(text.trim(), 40);
+        assert_eq!(output, "word word word word word word word…");
+
+        assert_eq!(truncate_description("short", 160), "short");
+    }
+
+    #[test]
+    fn test_split_readme_empty() {
+        let parts = split_readme("");
+        assert_eq!(parts.title, None);
+        assert_eq!(parts.body, "");
+    }
+
+    fn no_pages(_: &str) -> Option<String> {
+        None
+    }
+
+    fn pages(code: &str) -> Option<String> {
+        (code == "COMP2001").then(|| "/docs/2023/080901/fresh-autumn/COMP2001".to_string())
+    }
+
+    #[test]
+    fn test_rewrite_relative_markdown_link() {
+        let output = rewrite_links("See [notes](./docs/notes.md).", "TEST101", no_pages);
+        assert_eq!(
+            output,
+            "See [notes](https://gh.hoa.moe/github.com/HITSZ-OpenAuto/TEST101/raw/main/docs/notes.md)."
+        );
+    }
+
+    #[test]
+    fn test_rewrite_relative_image() {
+        let output = rewrite_links("![](images/a.png)", "TEST101", no_pages);
+        assert_eq!(
+            output,
+            "![](https://gh.hoa.moe/github.com/HITSZ-OpenAuto/TEST101/raw/main/images/a.png)"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_keeps_title_and_fragment() {
+        let output = rewrite_links(r#"[a](a.pdf#page=2 "Title")"#, "TEST101", no_pages);
+        assert_eq!(
+            output,
+            r#"[a](https://gh.hoa.moe/github.com/HITSZ-OpenAuto/TEST101/raw/main/a.pdf#page=2 "Title")"#
+        );
+    }
+
+    #[test]
+    fn test_rewrite_encoded_and_chinese_paths() {
+        let output = rewrite_links("[hw](作业/file%20name.pdf)", "TEST101", no_pages);
+        assert!(output.contains("/raw/main/%E4%BD%9C%E4%B8%9A/file%20name.pdf"));
+    }
+
+    #[test]
+    fn test_rewrite_directory_link() {
+        let output = rewrite_links("[slides](slides/)", "TEST101", no_pages);
+        assert_eq!(
+            output,
+            "[slides](https://github.com/HITSZ-OpenAuto/TEST101/tree/main/slides)"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_html_attributes() {
+        let input = r#"<img src="img/a.png" width="50%"> <a href='docs/b.pdf'>b</a>"#;
+        let output = rewrite_links(input, "TEST101", no_pages);
+        assert!(output.contains(
+            r#"<img src="https://gh.hoa.moe/github.com/HITSZ-OpenAuto/TEST101/raw/main/img/a.png" width="50%">"#
+        ));
+        assert!(output.contains(
+            "<a href='https://gh.hoa.moe/github.com/HITSZ-OpenAuto/TEST101/raw/main/docs/b.pdf'>"
+        ));
+    }
+
+    #[test]
+    fn test_rewrite_reference_definition() {
+        let output = rewrite_links("[ref]: ./a.pdf", "TEST101", no_pages);
+        assert_eq!(
+            output,
+            "[ref]: https://gh.hoa.moe/github.com/HITSZ-OpenAuto/TEST101/raw/main/a.pdf"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_course_repo_links() {
+        let input = "[a](https://github.com/HITSZ-OpenAuto/COMP2001) [b](../COMP2001)";
+        let output = rewrite_links(input, "TEST101", pages);
+        assert_eq!(
+            output,
+            "[a](/docs/2023/080901/fresh-autumn/COMP2001) [b](/docs/2023/080901/fresh-autumn/COMP2001)"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_leaves_unknown_and_absolute_links() {
+        let input = "[a](https://github.com/HITSZ-OpenAuto/UNKNOWN) [b](https://example.com/x.png) [c](#section) [d](mailto:a@b.c) [e](../../x)";
+        let output = rewrite_links(input, "TEST101", pages);
+        assert_eq!(output, input);
+    }
+
+    #[test]
+    fn test_rewrite_nested_image_link() {
+        let output = rewrite_links("[![logo](logo.png)](docs/)", "TEST101", no_pages);
+        assert!(output
+            .contains("(https://gh.hoa.moe/github.com/HITSZ-OpenAuto/TEST101/raw/main/logo.png)"));
+        assert!(output.contains("(https://github.com/HITSZ-OpenAuto/TEST101/tree/main/docs)"));
+    }
+
+    #[test]
+    fn test_rewrite_ignores_code_blocks() {
+        let input = "```markdown\n[a](./a.md)\n```\n[b](./b.md)";
+        let output = rewrite_links(input, "TEST101", no_pages);
+        assert!(output.contains("```markdown\n[a](./a.md)\n```"));
+        assert!(output.contains("/raw/main/b.md"));
+    }
+
+    #[test]
+    fn test_rewrite_ignores_inline_code() {
+        let input = "Write `[x](y.md)` or ``[a](`b`.md)``, see [c](c.md).";
+        let output = rewrite_links(input, "TEST101", no_pages);
+        assert!(output.starts_with("Write `[x](y.md)` or ``[a](`b`.md)``, see [c](https://"));
+        assert!(output.contains("/raw/main/c.md"));
+    }
+
+    #[test]
+    fn test_rewrite_ignores_tilde_fences() {
+        let input = "~~~md\n[x](y.md)\n~~~\n\n[c](c.md)";
+        let output = rewrite_links(input, "TEST101", no_pages);
+        assert!(output.starts_with("~~~md\n[x](y.md)\n~~~\n\n[c](https://"));
+    }
+}
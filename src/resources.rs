@@ -0,0 +1,230 @@
+//! Global index of the resource files of every course.
+//!
+//! Students often look for a file (e.g. a past exam) without knowing its course.
+//! Every file of every course worktree is listed with its course, path, size, date,
+//! download URL, file type and category (see [`crate::classify`]), as the
+//! `resources/` page and `public/data/resources.json`.
+
+use crate::classify::{file_type, Category, FileType};
+use crate::error::Result;
+use crate::generator::{course_page_href, has_course_page, yaml_string};
+use crate::models::{latest_course_names, Plan};
+use crate::tree::{flatten_files, FileTrees};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// Site path of the resources JSON, loaded by the `<ResourceTable>` component
+const RESOURCES_JSON: &str = "/data/resources.json";
+
+/// A resource file of a course
+#[derive(Debug, Serialize)]
+pub struct ResourceEntry {
+    pub code: String,
+    /// Course name, or the code for unnamed courses
+    pub name: String,
+    /// Site path of the course page, if the course has one
+    pub href: Option<String>,
+    /// Path of the file in the course repository
+    pub path: String,
+    pub size: Option<u64>,
+    pub date: Option<String>,
+    pub url: Option<String>,
+    pub file_type: FileType,
+    pub category: Category,
+}
+
+/// JSON artifact of the resources index
+#[derive(Debug, Serialize)]
+struct ResourcesIndex<'a> {
+    total: usize,
+    resources: &'a [ResourceEntry],
+}
+
+/// Build the entries of every file of every course in the plans, by course code.
+///
+/// Only courses in repos_list (if repos_list.txt exists) are listed. Courses are
+/// named after their most recent plan and link to their page if they have one.
+pub fn build_resources(
+    plans: &[Plan],
+    repos_dir: &Path,
+    repos_set: &HashSet<String>,
+    trees: &FileTrees,
+) -> Vec<ResourceEntry> {
+    let names = latest_course_names(plans);
+
+    let mut entries = Vec::new();
+    for (code, name) in names {
+        if !(repos_set.is_empty() || repos_set.contains(code)) {
+            continue;
+        }
+        let Some(tree) = trees.get(code) else {
+            continue;
+        };
+
+        let name = match name.trim() {
+            "" => code,
+            name => name,
+        };
+        let href = has_course_page(code, repos_dir, repos_set).then(|| course_page_href(code));
+        entries.extend(
+            flatten_files(&tree.nodes)
+                .into_iter()
+                .map(|(path, node)| ResourceEntry {
+                    code: code.to_string(),
+                    name: name.to_string(),
+                    href: href.clone(),
+                    file_type: file_type(&path),
+                    path,
+                    size: node.size,
+                    date: node.date.clone(),
+                    url: node.url.clone(),
+                    category: node.category.unwrap_or(Category::Other),
+                }),
+        );
+    }
+
+    entries
+}
+
+/// Build the resources page: a summary by category and the `<ResourceTable>`
+/// component, which loads the files from `resources.json` on the client
+pub fn build_resources_page(entries: &[ResourceEntry]) -> String {
+    let courses: HashSet<&str> = entries.iter().map(|e| e.code.as_str()).collect();
+    let summary = format!(
+        "共 {} 个资料文件，来自 {} 门课程",
+        entries.len(),
+        courses.len()
+    );
+
+    let counts: Vec<String> = Category::ALL
+        .into_iter()
+        .filter_map(|category| {
+            let count = entries.iter().filter(|e| e.category == category).count();
+            (count > 0).then(|| format!("{} {}", category.label(), count))
+        })
+        .collect();
+    let by_category = if counts.is_empty() {
+        String::new()
+    } else {
+        format!("：{}", counts.join("、"))
+    };
+
+    [
+        "---".to_string(),
+        "title: 资料索引".to_string(),
+        format!("description: {}", yaml_string(&summary)),
+        "---".to_string(),
+        "".to_string(),
+        format!(
+            "{}{}。分类根据文件路径推断，完整数据见 [resources.json]({})。",
+            summary, by_category, RESOURCES_JSON
+        ),
+        "".to_string(),
+        format!("<ResourceTable src=\"{}\" />", RESOURCES_JSON),
+    ]
+    .join("\n")
+}
+
+/// Generate `resources/index.mdx` and `public/data/resources.json`
+pub fn generate_resources_index(
+    plans: &[Plan],
+    repos_dir: &Path,
+    repos_set: &HashSet<String>,
+    trees: &FileTrees,
+    docs_dir: &Path,
+    public_dir: &Path,
+) -> Result<()> {
+    let entries = build_resources(plans, repos_dir, repos_set, trees);
+
+    let resources_dir = docs_dir.join("resources");
+    fs::create_dir_all(&resources_dir)?;
+    let resources_meta = serde_json::json!({
+        "title": "资料索引",
+        "root": true,
+    });
+    fs::write(
+        resources_dir.join("meta.json"),
+        serde_json::to_string_pretty(&resources_meta)?,
+    )?;
+    fs::write(
+        resources_dir.join("index.mdx"),
+        build_resources_page(&entries),
+    )?;
+
+    let data_dir = public_dir.join("data");
+    fs::create_dir_all(&data_dir)?;
+    // Compact, as the frontend downloads it as a whole
+    fs::write(
+        data_dir.join("resources.json"),
+        serde_json::to_string(&ResourcesIndex {
+            total: entries.len(),
+            resources: &entries,
+        })?,
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ignore::IgnoreRules;
+    use crate::loader::load_file_trees;
+    use crate::models::test_support::{course, plan};
+
+    #[test]
+    fn test_build_resources_page() {
+        let temp_dir = std::env::temp_dir().join("test_build_resources_page");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        fs::write(
+            temp_dir.join("MATH1001.json"),
+            r#"{"试卷/2022期末.pdf": {"size": 2048, "time": 0}, "a_b.pdf": {"size": null, "time": null}, "README.md": {"size": 1, "time": null}}"#,
+        )
+        .unwrap();
+
+        fs::write(temp_dir.join("MATH1001.mdx"), "# 高等数学").unwrap();
+        fs::write(
+            temp_dir.join("PHYS1001.json"),
+            r#"{"a.pdf": {"size": 1, "time": null}}"#,
+        )
+        .unwrap();
+
+        let plans = vec![plan(
+            "2023",
+            "080901",
+            vec![
+                course("MATH1001").with_name("高等数学"),
+                course("PHYS1001").with_name("大学物理"),
+            ],
+        )];
+
+        // Courses left out of repos_list are skipped despite their worktree
+        let repos_set = HashSet::from(["MATH1001".to_string()]);
+        let trees = load_file_trees(&plans, &temp_dir, &IgnoreRules::builtin()).unwrap();
+        let entries = build_resources(&plans, &temp_dir, &repos_set, &trees);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].path, "试卷/2022期末.pdf");
+        assert_eq!(entries[0].file_type, FileType::Pdf);
+        assert_eq!(
+            serde_json::to_value(&entries[0]).unwrap()["file_type"],
+            "pdf"
+        );
+        assert_eq!(entries[0].category, Category::Exam);
+        assert_eq!(entries[0].date.as_deref(), Some("1970-01-01"));
+        assert_eq!(entries[1].path, "a_b.pdf");
+        assert_eq!(entries[1].category, Category::Other);
+        assert_eq!(entries[0].name, "高等数学");
+        assert_eq!(entries[0].href.as_deref(), Some("/docs/courses/MATH1001"));
+
+        let page = build_resources_page(&entries);
+        assert!(page.contains("description: \"共 2 个资料文件，来自 1 门课程\""));
+        assert!(page.contains("来自 1 门课程：试卷 1、其他 1。"));
+        assert!(page.ends_with("\n<ResourceTable src=\"/data/resources.json\" />"));
+        assert!(!page.contains('|'));
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+}
@@ -0,0 +1,183 @@
+//! Prebuilt client-side search index.
+//!
+//! Fumadocs search indexes every generated page, so a course appearing in many
+//! plans shows up many times. This module writes one compact entry per course
+//! with a page to `public/data/search.json`: code, name, pinyin and initials of the
+//! name, README headings and resource file names, for instant fuzzy search in the
+//! frontend.
+
+use crate::error::Result;
+use crate::generator::{course_page_href, has_course_page};
+use crate::models::{latest_course_names, FileNode, NodeType, Plan};
+use crate::pinyin;
+use crate::readme::{extract_headings, split_readme};
+use crate::tree::FileTrees;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::path::Path;
+
+/// A course in the search index
+#[derive(Debug, Serialize)]
+pub struct SearchEntry {
+    pub code: String,
+    /// Name in the most recent plan including the course
+    pub name: String,
+    /// Full pinyin of the name, e.g. `gaodengshuxue`
+    pub pinyin: String,
+    /// Pinyin initials of the name, e.g. `gdsx`
+    pub initials: String,
+    pub headings: Vec<String>,
+    /// Distinct names of the resource files
+    pub files: Vec<String>,
+    pub href: String,
+}
+
+/// JSON artifact of the search index
+#[derive(Debug, Serialize)]
+struct SearchIndex<'a> {
+    courses: &'a [SearchEntry],
+}
+
+/// Collect distinct file names of a file tree, in tree order
+fn collect_file_names<'a>(
+    nodes: &'a [FileNode],
+    seen: &mut HashSet<&'a str>,
+    names: &mut Vec<String>,
+) {
+    for node in nodes {
+        match node.node_type {
+            NodeType::Folder => collect_file_names(&node.children, seen, names),
+            NodeType::File => {
+                if seen.insert(&node.name) {
+                    names.push(node.name.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Build the search entries of every course with a page, sorted by course code
+pub fn build_search_index(
+    plans: &[Plan],
+    repos_dir: &Path,
+    repos_set: &HashSet<String>,
+    trees: &FileTrees,
+) -> Result<Vec<SearchEntry>> {
+    let names = latest_course_names(plans);
+    let codes: BTreeSet<&str> = names
+        .keys()
+        .copied()
+        .filter(|code| has_course_page(code, repos_dir, repos_set))
+        .collect();
+
+    let mut entries = Vec::new();
+    for code in codes {
+        let readme = split_readme(&fs::read_to_string(
+            repos_dir.join(format!("{}.mdx", code)),
+        )?);
+        let name = match names[code].trim() {
+            "" => readme.title.clone().unwrap_or_else(|| code.to_string()),
+            name => name.to_string(),
+        };
+
+        let mut files = Vec::new();
+        if let Some(tree) = trees.get(code) {
+            collect_file_names(&tree.nodes, &mut HashSet::new(), &mut files);
+        }
+
+        entries.push(SearchEntry {
+            code: code.to_string(),
+            pinyin: pinyin::full(&name),
+            initials: pinyin::initials(&name),
+            name,
+            headings: extract_headings(&readme.body),
+            files,
+            href: course_page_href(code),
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Generate `public/data/search.json`
+pub fn generate_search_index(
+    plans: &[Plan],
+    repos_dir: &Path,
+    repos_set: &HashSet<String>,
+    trees: &FileTrees,
+    public_dir: &Path,
+) -> Result<()> {
+    let entries = build_search_index(plans, repos_dir, repos_set, trees)?;
+
+    let data_dir = public_dir.join("data");
+    fs::create_dir_all(&data_dir)?;
+    // Compact, as the frontend downloads it as a whole
+    fs::write(
+        data_dir.join("search.json"),
+        serde_json::to_string(&SearchIndex { courses: &entries })?,
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ignore::IgnoreRules;
+    use crate::loader::load_file_trees;
+    use crate::models::test_support::{course, plan as plan_of};
+
+    fn plan(year: &str, courses: &[(&str, &str)]) -> Plan {
+        let courses = courses
+            .iter()
+            .map(|(code, name)| course(code).with_name(name))
+            .collect();
+        plan_of(year, "080901", courses).with_major_name("计算机")
+    }
+
+    #[test]
+    fn test_build_search_index() {
+        let temp_dir = std::env::temp_dir().join("test_build_search_index");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        fs::write(
+            temp_dir.join("MATH1001.mdx"),
+            "# 高等数学\n\n## 课程资料\n\n### 往年试卷\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.join("MATH1001.json"),
+            r#"{"exam/2022期末.pdf": {"size": 1, "time": null}, "notes/2022期末.pdf": {"size": 1, "time": null}, "notes/笔记.pdf": {"size": 1, "time": null}}"#,
+        )
+        .unwrap();
+        fs::write(temp_dir.join("COMP1001.mdx"), "# 程序设计\n").unwrap();
+
+        let plans = vec![
+            plan("2022", &[("MATH1001", "旧名"), ("COMP1001", "")]),
+            plan(
+                "2023",
+                &[("MATH1001", "高等数学"), ("PHYS1001", "大学物理")],
+            ),
+        ];
+
+        let trees = load_file_trees(&plans, &temp_dir, &IgnoreRules::default()).unwrap();
+        let entries = build_search_index(&plans, &temp_dir, &HashSet::new(), &trees).unwrap();
+        assert_eq!(entries.len(), 2);
+
+        // Unnamed courses fall back to the README title
+        assert_eq!(entries[0].code, "COMP1001");
+        assert_eq!(entries[0].name, "程序设计");
+        assert!(entries[0].files.is_empty());
+
+        let math = &entries[1];
+        assert_eq!(math.name, "高等数学");
+        assert_eq!(math.pinyin, "gaodengshuxue");
+        assert_eq!(math.initials, "gdsx");
+        assert_eq!(math.headings, vec!["课程资料", "往年试卷"]);
+        assert_eq!(math.files, vec!["2022期末.pdf", "笔记.pdf"]);
+        assert_eq!(math.href, "/docs/courses/MATH1001");
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+}
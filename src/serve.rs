@@ -0,0 +1,505 @@
+//! Read-only HTTP JSON API over the loaded plans.
+//!
+//! The `serve` command loads the plans once and answers the same documents as the
+//! static API in [`crate::api`], plus queries that need a server: filtering the
+//! courses of a plan and searching courses by code or name. Paths match the static
+//! tree, with or without the `.json` suffix, so links in documents work with both.
+
+use crate::api::{self, API_BASE};
+use crate::config::{Config, SemestersConfig};
+use crate::error::{FumaError, Result};
+use crate::ignore::IgnoreRules;
+use crate::models::{latest_course_names, Course, Plan};
+use crate::{generator, loader};
+use axum::extract::rejection::QueryRejection;
+use axum::extract::{Path as UrlPath, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Default address the server listens on
+const DEFAULT_HOST: &str = "127.0.0.1";
+const DEFAULT_PORT: u16 = 3000;
+
+/// Maximum number of search results
+const MAX_SEARCH_RESULTS: usize = 50;
+
+/// Data shared by all requests, loaded once at startup
+pub struct ServerState {
+    pub plans: Vec<Plan>,
+    pub semesters: SemestersConfig,
+    /// Course documents with their file trees, by course code
+    courses: HashMap<String, serde_json::Value>,
+}
+
+impl ServerState {
+    /// Build the state of the plans, reading the worktree of every course once
+    pub fn new(
+        plans: Vec<Plan>,
+        repos_dir: &Path,
+        repos_set: &HashSet<String>,
+        exclude: &IgnoreRules,
+        semesters: SemestersConfig,
+    ) -> Result<Self> {
+        let trees = loader::load_file_trees(&plans, repos_dir, exclude)?;
+        let mut courses = HashMap::new();
+        for (code, occurrences) in generator::index_occurrences(&plans) {
+            if let Some(document) =
+                api::course_document(code, &occurrences, repos_dir, repos_set, &trees)
+            {
+                courses.insert(code.to_string(), serde_json::to_value(document)?);
+            }
+        }
+
+        Ok(Self {
+            plans,
+            semesters,
+            courses,
+        })
+    }
+}
+
+/// Options of the `serve` command
+#[derive(Debug, PartialEq)]
+pub struct ServeOptions {
+    pub host: String,
+    pub port: u16,
+}
+
+impl ServeOptions {
+    /// Parse the arguments following `serve`
+    pub fn parse(args: &[String]) -> Result<Self> {
+        let mut options = Self {
+            host: DEFAULT_HOST.to_string(),
+            port: DEFAULT_PORT,
+        };
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--host" => {
+                    options.host = args
+                        .next()
+                        .ok_or_else(|| {
+                            FumaError::InvalidArgument("--host requires an address".to_string())
+                        })?
+                        .clone();
+                }
+                "--port" => {
+                    options.port =
+                        args.next()
+                            .and_then(|port| port.parse().ok())
+                            .ok_or_else(|| {
+                                FumaError::InvalidArgument(
+                                    "--port requires a port number".to_string(),
+                                )
+                            })?;
+                }
+                other => {
+                    return Err(FumaError::InvalidArgument(format!(
+                        "unknown serve option: {}",
+                        other
+                    )));
+                }
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+/// Error response of a request
+enum ApiError {
+    BadRequest(String),
+    NotFound(String),
+    Internal(FumaError),
+}
+
+impl From<FumaError> for ApiError {
+    fn from(e: FumaError) -> Self {
+        Self::Internal(e)
+    }
+}
+
+impl From<QueryRejection> for ApiError {
+    fn from(rejection: QueryRejection) -> Self {
+        Self::BadRequest(rejection.body_text())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            Self::BadRequest(message) => (StatusCode::BAD_REQUEST, message),
+            Self::NotFound(message) => (StatusCode::NOT_FOUND, message),
+            Self::Internal(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        };
+        (status, Json(serde_json::json!({ "error": message }))).into_response()
+    }
+}
+
+type ApiResult = std::result::Result<Response, ApiError>;
+
+/// Path parameter without the optional `.json` suffix of the static tree
+fn strip_json(param: &str) -> &str {
+    param.strip_suffix(".json").unwrap_or(param)
+}
+
+async fn years(State(state): State<Arc<ServerState>>) -> ApiResult {
+    let by_year = api::plans_by_year(&state.plans);
+    Ok(Json(api::years_document(&by_year)).into_response())
+}
+
+async fn majors(
+    State(state): State<Arc<ServerState>>,
+    UrlPath(year): UrlPath<String>,
+) -> ApiResult {
+    let by_year = api::plans_by_year(&state.plans);
+    let (year, year_plans) = by_year
+        .get_key_value(year.as_str())
+        .ok_or_else(|| ApiError::NotFound(format!("no plans for year {}", year)))?;
+    Ok(Json(api::majors_document(year, year_plans)).into_response())
+}
+
+/// Filters on the courses of a plan
+#[derive(Debug, Default, Deserialize)]
+pub struct CourseFilter {
+    /// Semester folder (`fresh-autumn`), title (`大一·秋`) or raw plan value
+    pub semester: Option<String>,
+    /// Exact course nature
+    pub nature: Option<String>,
+    /// Minimum credit, inclusive
+    pub min_credit: Option<f64>,
+}
+
+impl CourseFilter {
+    fn matches(&self, course: &Course, semesters: &SemestersConfig) -> bool {
+        if let Some(semester) = &self.semester {
+            let raw = course.recommended_semester.as_deref();
+            let placed = semesters
+                .placements(raw)
+                .iter()
+                .any(|s| s.folder == *semester || s.title == *semester);
+            if !placed && raw != Some(semester.as_str()) {
+                return false;
+            }
+        }
+
+        if let Some(nature) = &self.nature {
+            if course.course_nature.as_deref() != Some(nature.as_str()) {
+                return false;
+            }
+        }
+
+        if let Some(min_credit) = self.min_credit {
+            if course.credit.is_none_or(|credit| credit < min_credit) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+async fn plan(
+    State(state): State<Arc<ServerState>>,
+    UrlPath((year, major)): UrlPath<(String, String)>,
+    filter: std::result::Result<Query<CourseFilter>, QueryRejection>,
+) -> ApiResult {
+    let Query(filter) = filter?;
+    let major = strip_json(&major);
+    let plan = state
+        .plans
+        .iter()
+        .find(|p| p.year == year && p.major_code == major)
+        .ok_or_else(|| ApiError::NotFound(format!("no plan for {} {}", year, major)))?;
+
+    let courses = plan
+        .courses
+        .iter()
+        .filter(|c| filter.matches(c, &state.semesters));
+    Ok(Json(api::plan_document(plan, courses)).into_response())
+}
+
+async fn course(
+    State(state): State<Arc<ServerState>>,
+    UrlPath(code): UrlPath<String>,
+) -> ApiResult {
+    let code = strip_json(&code);
+    let document = state
+        .courses
+        .get(code)
+        .ok_or_else(|| ApiError::NotFound(format!("no course {}", code)))?;
+    Ok(Json(document).into_response())
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchQuery {
+    q: Option<String>,
+}
+
+async fn search(
+    State(state): State<Arc<ServerState>>,
+    query: std::result::Result<Query<SearchQuery>, QueryRejection>,
+) -> ApiResult {
+    let Query(query) = query?;
+    let q = query
+        .q
+        .ok_or_else(|| ApiError::BadRequest("missing query parameter q".to_string()))?;
+    Ok(Json(search_courses(&state.plans, &q)).into_response())
+}
+
+/// Search courses by code or name, case-insensitively.
+///
+/// Exact code matches come first, then code prefixes, then other matches; each
+/// course appears once, named after its most recent plan.
+pub fn search_courses(plans: &[Plan], query: &str) -> serde_json::Value {
+    let needle = query.trim().to_lowercase();
+
+    let mut results: Vec<(u8, &str, &str)> = if needle.is_empty() {
+        Vec::new()
+    } else {
+        latest_course_names(plans)
+            .into_iter()
+            .filter_map(|(code, name)| {
+                let lower = code.to_lowercase();
+                let rank = if lower == needle {
+                    0
+                } else if lower.starts_with(&needle) {
+                    1
+                } else if lower.contains(&needle) || name.to_lowercase().contains(&needle) {
+                    2
+                } else {
+                    return None;
+                };
+                Some((rank, code, name))
+            })
+            .collect()
+    };
+    results.sort();
+    results.truncate(MAX_SEARCH_RESULTS);
+
+    serde_json::json!({
+        "query": query,
+        "results": results
+            .iter()
+            .map(|(_, code, name)| serde_json::json!({
+                "code": code,
+                "name": name,
+                "href": format!("{}/courses/{}.json", API_BASE, code),
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// Build the router of the API
+pub fn router(state: Arc<ServerState>) -> Router {
+    Router::new()
+        .route(&format!("{}/years", API_BASE), get(years))
+        .route(&format!("{}/years.json", API_BASE), get(years))
+        .route(&format!("{}/years/{{year}}/majors", API_BASE), get(majors))
+        .route(
+            &format!("{}/years/{{year}}/majors.json", API_BASE),
+            get(majors),
+        )
+        .route(&format!("{}/plans/{{year}}/{{major}}", API_BASE), get(plan))
+        .route(&format!("{}/courses/{{code}}", API_BASE), get(course))
+        .route(&format!("{}/search", API_BASE), get(search))
+        .with_state(state)
+}
+
+/// Run the `serve` command until interrupted
+pub async fn run(repo_root: &Path, args: &[String]) -> Result<()> {
+    let options = ServeOptions::parse(args)?;
+
+    let config = Config::load(repo_root)?;
+    let plans = loader::load_all_plans(&repo_root.join("hoa-major-data"))?;
+    println!("Loaded {} training plans", plans.len());
+
+    let state = Arc::new(ServerState::new(
+        plans,
+        &repo_root.join("repos"),
+        &loader::load_repos_list(repo_root)?,
+        &config.file_tree.exclude_rules(),
+        config.semesters,
+    )?);
+
+    let address = format!("{}:{}", options.host, options.port);
+    let listener = tokio::net::TcpListener::bind(&address).await?;
+    println!("Serving API on http://{}{}", address, API_BASE);
+
+    axum::serve(listener, router(state)).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support;
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    fn course(code: &str, name: &str, semester: &str, credit: f64, nature: &str) -> Course {
+        test_support::course(code)
+            .with_name(name)
+            .with_semester(semester)
+            .with_credit(credit)
+            .with_nature(nature)
+    }
+
+    fn plan(year: &str, courses: Vec<Course>) -> Plan {
+        test_support::plan(year, "080901", courses).with_major_name("计算机")
+    }
+
+    fn state() -> Arc<ServerState> {
+        let plans = vec![
+            plan(
+                "2022",
+                vec![course("MATH1001", "旧名", "第一学年秋季", 5.0, "必修")],
+            ),
+            plan(
+                "2023",
+                vec![
+                    course("MATH1001", "高等数学", "第一学年秋季", 5.5, "必修"),
+                    course("MATH2001", "线性代数", "第一学年春季", 2.0, "必修"),
+                    course("COMP1001", "程序设计", "第一学年秋季", 3.0, "选修"),
+                ],
+            ),
+        ];
+
+        Arc::new(
+            ServerState::new(
+                plans,
+                &std::env::temp_dir().join("test_serve_missing_repos"),
+                &HashSet::new(),
+                &IgnoreRules::default(),
+                SemestersConfig::default(),
+            )
+            .unwrap(),
+        )
+    }
+
+    async fn get_json(uri: &str) -> (StatusCode, serde_json::Value) {
+        let response = router(state())
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    fn codes(document: &serde_json::Value) -> Vec<String> {
+        document["courses"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["code"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_options() {
+        assert_eq!(
+            ServeOptions::parse(&[]).unwrap(),
+            ServeOptions {
+                host: "127.0.0.1".to_string(),
+                port: 3000
+            }
+        );
+
+        let args: Vec<String> = ["--port", "8080", "--host", "0.0.0.0"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let options = ServeOptions::parse(&args).unwrap();
+        assert_eq!((options.host.as_str(), options.port), ("0.0.0.0", 8080));
+
+        assert!(matches!(
+            ServeOptions::parse(&["--port".to_string(), "x".to_string()]),
+            Err(FumaError::InvalidArgument(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_years_and_majors() {
+        let (status, years) = get_json("/api/v1/years").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(years["years"][0]["year"], "2023");
+
+        let (_, majors) = get_json("/api/v1/years/2023/majors.json").await;
+        assert_eq!(majors["majors"][0]["course_count"], 3);
+
+        let (status, error) = get_json("/api/v1/years/1999/majors").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(error["error"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_plan_course_filters() {
+        let (_, all) = get_json("/api/v1/plans/2023/080901.json").await;
+        assert_eq!(codes(&all).len(), 3);
+
+        let (_, autumn) = get_json("/api/v1/plans/2023/080901?semester=fresh-autumn").await;
+        assert_eq!(codes(&autumn), vec!["MATH1001", "COMP1001"]);
+
+        let (_, titled) =
+            get_json("/api/v1/plans/2023/080901?semester=%E5%A4%A7%E4%B8%80%C2%B7%E6%98%A5").await;
+        assert_eq!(codes(&titled), vec!["MATH2001"]);
+
+        let (_, filtered) =
+            get_json("/api/v1/plans/2023/080901?nature=%E5%BF%85%E4%BF%AE&min_credit=3").await;
+        assert_eq!(codes(&filtered), vec!["MATH1001"]);
+
+        let (status, _) = get_json("/api/v1/plans/2023/000000").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, error) = get_json("/api/v1/plans/2023/080901?min_credit=abc").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(error["error"].as_str().unwrap().contains("min_credit"));
+    }
+
+    #[tokio::test]
+    async fn test_course_detail() {
+        let (status, math) = get_json("/api/v1/courses/MATH1001.json").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(math["name"], "高等数学");
+        assert_eq!(math["plans"].as_array().unwrap().len(), 2);
+        assert_eq!(math["files"], serde_json::json!([]));
+
+        let (status, _) = get_json("/api/v1/courses/NONE0000").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_search() {
+        let (_, results) = get_json("/api/v1/search?q=math").await;
+        let found: Vec<&str> = results["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["code"].as_str().unwrap())
+            .collect();
+        assert_eq!(found, vec!["MATH1001", "MATH2001"]);
+        assert_eq!(results["results"][0]["name"], "高等数学");
+
+        let (_, by_name) = get_json("/api/v1/search?q=%E7%A8%8B%E5%BA%8F").await;
+        assert_eq!(by_name["results"][0]["code"], "COMP1001");
+
+        let (_, exact) = get_json("/api/v1/search?q=math2001").await;
+        assert_eq!(exact["results"][0]["code"], "MATH2001");
+
+        let (_, empty) = get_json("/api/v1/search?q=").await;
+        assert_eq!(empty["results"], serde_json::json!([]));
+
+        let (status, error) = get_json("/api/v1/search").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(error["error"].is_string());
+    }
+}
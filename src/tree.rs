@@ -1,6 +1,9 @@
-use crate::constants::should_include_file;
+use crate::classify::{category, file_type, Category, FileType};
+use crate::config::{FileOrder, FileTreeConfig};
 use crate::models::{FileNode, NodeType, WorktreeData};
-use std::collections::HashMap;
+use crate::natural::natural_cmp;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
 
 /// Format Unix timestamp to YYYY-MM-DD format
 fn format_timestamp(unix_ts: i64) -> String {
@@ -12,7 +15,7 @@ fn format_timestamp(unix_ts: i64) -> String {
 }
 
 /// Generate download URL for a file in the repository
-fn generate_download_url(repo: &str, path: &str) -> String {
+pub fn generate_download_url(repo: &str, path: &str) -> String {
     // Only encode parts, not the path separators
     let parts: Vec<String> = path
         .split('/')
@@ -25,6 +28,25 @@ fn generate_download_url(repo: &str, path: &str) -> String {
     )
 }
 
+/// Worktree of a course after exclusions, with the file tree built from it
+#[derive(Debug)]
+pub struct FileTree {
+    /// Flat worktree entries by path
+    pub worktree: WorktreeData,
+    /// Files and folders, folders first and by name
+    pub nodes: Vec<FileNode>,
+}
+
+impl FileTree {
+    pub fn new(worktree: WorktreeData, code: &str) -> Self {
+        let nodes = build_file_tree(&worktree, code);
+        Self { worktree, nodes }
+    }
+}
+
+/// File trees of the courses with worktree data, by course code
+pub type FileTrees = BTreeMap<String, FileTree>;
+
 /// Build nested file tree from flat worktree data
 pub fn build_file_tree(flat_data: &WorktreeData, repo_name: &str) -> Vec<FileNode> {
     #[derive(Debug)]
@@ -34,6 +56,8 @@ pub fn build_file_tree(flat_data: &WorktreeData, repo_name: &str) -> Vec<FileNod
         url: Option<String>,
         size: Option<u64>,
         date: Option<String>,
+        file_type: Option<FileType>,
+        category: Option<Category>,
     }
 
     impl TreeBuilder {
@@ -44,6 +68,8 @@ pub fn build_file_tree(flat_data: &WorktreeData, repo_name: &str) -> Vec<FileNod
                 url: None,
                 size: None,
                 date: None,
+                file_type: None,
+                category: None,
             }
         }
 
@@ -54,24 +80,35 @@ pub fn build_file_tree(flat_data: &WorktreeData, repo_name: &str) -> Vec<FileNod
                 .map(|(child_name, builder)| builder.into_node(child_name))
                 .collect();
 
-            // Sort: folders first, then by name
-            children.sort_by(|a, b| match (&a.node_type, &b.node_type) {
-                (NodeType::Folder, NodeType::File) => std::cmp::Ordering::Less,
-                (NodeType::File, NodeType::Folder) => std::cmp::Ordering::Greater,
-                _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
-            });
+            children.sort_by(|a, b| compare_nodes(a, b, FileOrder::Name));
 
+            if self.is_file {
+                return FileNode {
+                    name,
+                    node_type: NodeType::File,
+                    children,
+                    url: self.url,
+                    size: self.size,
+                    date: self.date,
+                    file_count: None,
+                    file_type: self.file_type,
+                    category: self.category,
+                };
+            }
+
+            // Folders aggregate the files below them
+            let (file_count, size) = summarize_files(&children);
+            let date = children.iter().filter_map(|c| c.date.clone()).max();
             FileNode {
                 name,
-                node_type: if self.is_file {
-                    NodeType::File
-                } else {
-                    NodeType::Folder
-                },
+                node_type: NodeType::Folder,
                 children,
-                url: self.url,
-                size: self.size,
-                date: self.date,
+                url: None,
+                size: Some(size),
+                date,
+                file_count: Some(file_count),
+                file_type: None,
+                category: None,
             }
         }
     }
@@ -80,10 +117,6 @@ pub fn build_file_tree(flat_data: &WorktreeData, repo_name: &str) -> Vec<FileNod
 
     // Build tree from flat paths
     for (path, meta) in flat_data.0.iter() {
-        if !should_include_file(path) {
-            continue;
-        }
-
         let parts: Vec<&str> = path.split('/').collect();
         let mut current = &mut root;
 
@@ -99,6 +132,8 @@ pub fn build_file_tree(flat_data: &WorktreeData, repo_name: &str) -> Vec<FileNod
                 current.url = Some(generate_download_url(repo_name, path));
                 current.size = meta.size;
                 current.date = meta.time.map(format_timestamp);
+                current.file_type = Some(file_type(path));
+                current.category = Some(category(path));
             }
         }
     }
@@ -110,41 +145,168 @@ pub fn build_file_tree(flat_data: &WorktreeData, repo_name: &str) -> Vec<FileNod
         .map(|(name, builder)| builder.into_node(name))
         .collect();
 
-    result.sort_by(|a, b| match (&a.node_type, &b.node_type) {
-        (NodeType::Folder, NodeType::File) => std::cmp::Ordering::Less,
-        (NodeType::File, NodeType::Folder) => std::cmp::Ordering::Greater,
-        _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
-    });
+    result.sort_by(|a, b| compare_nodes(a, b, FileOrder::Name));
 
     result
 }
 
-/// Convert file tree to JSX string for Fumadocs Files component
-pub fn tree_to_jsx(nodes: &[FileNode], indent_level: usize) -> String {
+/// Compare nodes of a folder: folders first, then by the given order.
+///
+/// Folders compare by the aggregated date and size of their files; ties and
+/// nodes without a date or size fall back to natural name order.
+fn compare_nodes(a: &FileNode, b: &FileNode, order: FileOrder) -> Ordering {
+    let by_name = || natural_cmp(&a.name, &b.name);
+    match (&a.node_type, &b.node_type) {
+        (NodeType::Folder, NodeType::File) => Ordering::Less,
+        (NodeType::File, NodeType::Folder) => Ordering::Greater,
+        _ => match order {
+            FileOrder::Name => by_name(),
+            // `None` sorts before `Some`, so compare reversed for newest first
+            FileOrder::Date => b.date.cmp(&a.date).then_with(by_name),
+            FileOrder::Size => b.size.cmp(&a.size).then_with(by_name),
+        },
+    }
+}
+
+/// Sort a file tree and every folder in it
+pub fn sort_tree(nodes: &mut [FileNode], order: FileOrder) {
+    nodes.sort_by(|a, b| compare_nodes(a, b, order));
+    for node in nodes {
+        sort_tree(&mut node.children, order);
+    }
+}
+
+/// Count the files in a file tree and sum their sizes
+pub fn summarize_files(nodes: &[FileNode]) -> (usize, u64) {
+    nodes
+        .iter()
+        .fold((0, 0), |(count, size), node| match node.node_type {
+            NodeType::Folder => {
+                let (c, s) = summarize_files(&node.children);
+                (count + c, size + s)
+            }
+            NodeType::File => (count + 1, size + node.size.unwrap_or(0)),
+        })
+}
+
+/// Format a byte count for display, e.g. `1.5 MB`
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Props of a `<File>` element, shown under the given name
+fn file_props(node: &FileNode, name: &str) -> Vec<String> {
+    let mut props = vec![format!("name=\"{}\"", name)];
+    if let Some(ref url) = node.url {
+        props.push(format!("url=\"{}\"", url));
+    }
+    if let Some(ref date) = node.date {
+        props.push(format!("date=\"{}\"", date));
+    }
+    // Skip size if it's 0 or None
+    if let Some(size) = node.size {
+        if size > 0 {
+            props.push(format!("size={{{}}}", size));
+        }
+    }
+    // Type and category let the frontend pick an icon and filter files
+    if let Some(file_type) = node.file_type {
+        props.push(format!("type=\"{}\"", variant_name(&file_type)));
+    }
+    if let Some(category) = node.category {
+        props.push(format!("category=\"{}\"", variant_name(&category)));
+    }
+    props
+}
+
+/// Serialized name of a unit enum variant, e.g. `pdf`
+fn variant_name<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// Whether a folder starts expanded.
+///
+/// Without any collapse rule folders keep the component default. Otherwise
+/// folders start expanded unless they hold more files or sit deeper than allowed.
+fn folder_open(node: &FileNode, depth: usize, config: &FileTreeConfig) -> Option<bool> {
+    if config.collapse_files.is_none() && config.collapse_depth.is_none() {
+        return None;
+    }
+
+    let too_many = config
+        .collapse_files
+        .is_some_and(|max| node.file_count.unwrap_or(0) > max);
+    let too_deep = config.collapse_depth.is_some_and(|max| depth > max);
+    Some(!too_many && !too_deep)
+}
+
+/// Props of a `<Folder>` element: its aggregates and whether it starts expanded
+fn folder_props(node: &FileNode, depth: usize, config: &FileTreeConfig) -> Vec<String> {
+    let mut props = vec![format!("name=\"{}\"", node.name)];
+    if let Some(file_count) = node.file_count {
+        props.push(format!("fileCount={{{}}}", file_count));
+    }
+    if let Some(size) = node.size {
+        if size > 0 {
+            props.push(format!("size={{{}}}", size));
+        }
+    }
+    if let Some(ref date) = node.date {
+        props.push(format!("date=\"{}\"", date));
+    }
+    if folder_open(node, depth, config) == Some(true) {
+        props.push("defaultOpen".to_string());
+    }
+    props
+}
+
+/// Convert file tree to JSX string for Fumadocs Files component.
+///
+/// Top-level nodes are at depth 1 for the collapse rules of `config`.
+pub fn tree_to_jsx(nodes: &[FileNode], indent_level: usize, config: &FileTreeConfig) -> String {
+    render_tree(nodes, indent_level, 1, config)
+}
+
+fn render_tree(
+    nodes: &[FileNode],
+    indent_level: usize,
+    depth: usize,
+    config: &FileTreeConfig,
+) -> String {
     let indent = "  ".repeat(indent_level);
     let mut result = Vec::new();
 
     for node in nodes {
         match node.node_type {
             NodeType::Folder => {
-                result.push(format!("{}<Folder name=\"{}\">", indent, node.name));
-                result.push(tree_to_jsx(&node.children, indent_level + 1));
+                let props = folder_props(node, depth, config);
+                result.push(format!("{}<Folder {}>", indent, props.join(" ")));
+                result.push(render_tree(
+                    &node.children,
+                    indent_level + 1,
+                    depth + 1,
+                    config,
+                ));
                 result.push(format!("{}</Folder>", indent));
             }
             NodeType::File => {
-                let mut props = vec![format!("name=\"{}\"", node.name)];
-                if let Some(ref url) = node.url {
-                    props.push(format!("url=\"{}\"", url));
-                }
-                if let Some(ref date) = node.date {
-                    props.push(format!("date=\"{}\"", date));
-                }
-                // Skip size if it's 0 or None
-                if let Some(size) = node.size {
-                    if size > 0 {
-                        props.push(format!("size={{{}}}", size));
-                    }
-                }
+                let props = file_props(node, &node.name);
                 result.push(format!("{}<File {} />", indent, props.join(" ")));
             }
         }
@@ -153,6 +315,56 @@ pub fn tree_to_jsx(nodes: &[FileNode], indent_level: usize) -> String {
     result.join("\n")
 }
 
+/// List the files of a tree with their paths, in tree order
+pub fn flatten_files(nodes: &[FileNode]) -> Vec<(String, &FileNode)> {
+    fn visit<'a>(nodes: &'a [FileNode], prefix: &str, files: &mut Vec<(String, &'a FileNode)>) {
+        for node in nodes {
+            let path = if prefix.is_empty() {
+                node.name.clone()
+            } else {
+                format!("{}/{}", prefix, node.name)
+            };
+            match node.node_type {
+                NodeType::Folder => visit(&node.children, &path, files),
+                NodeType::File => files.push((path, node)),
+            }
+        }
+    }
+
+    let mut files = Vec::new();
+    visit(nodes, "", &mut files);
+    files
+}
+
+/// Convert a file tree to JSX grouping its files into one folder per category.
+///
+/// Files are shown by their path since files of different folders are mixed.
+/// Empty categories are omitted.
+pub fn category_view_jsx(nodes: &[FileNode], indent_level: usize) -> String {
+    let indent = "  ".repeat(indent_level);
+    let files = flatten_files(nodes);
+
+    let mut result = Vec::new();
+    for category in Category::ALL {
+        let in_category: Vec<&(String, &FileNode)> = files
+            .iter()
+            .filter(|(_, node)| node.category.unwrap_or(Category::Other) == category)
+            .collect();
+        if in_category.is_empty() {
+            continue;
+        }
+
+        result.push(format!("{}<Folder name=\"{}\">", indent, category.label()));
+        for (path, node) in in_category {
+            let props = file_props(node, path);
+            result.push(format!("{}  <File {} />", indent, props.join(" ")));
+        }
+        result.push(format!("{}</Folder>", indent));
+    }
+
+    result.join("\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -254,39 +466,6 @@ mod tests {
         assert_eq!(tree[2].name, "z_file.txt");
     }
 
-    #[test]
-    fn test_exclusion_rules() {
-        let mut data = HashMap::new();
-        data.insert(
-            "README.md".to_string(),
-            FileMetadata {
-                size: Some(100),
-                time: None,
-            },
-        );
-        data.insert(
-            "valid.txt".to_string(),
-            FileMetadata {
-                size: Some(100),
-                time: None,
-            },
-        );
-        data.insert(
-            ".github/workflow.yml".to_string(),
-            FileMetadata {
-                size: Some(100),
-                time: None,
-            },
-        );
-
-        let worktree = WorktreeData(data);
-        let tree = build_file_tree(&worktree, "test-repo");
-
-        // Only valid.txt should remain
-        assert_eq!(tree.len(), 1);
-        assert_eq!(tree[0].name, "valid.txt");
-    }
-
     #[test]
     fn test_generate_download_url() {
         let url = generate_download_url("TEST101", "slides/lecture1.pdf");
@@ -323,9 +502,12 @@ mod tests {
             url: Some("https://example.com/test.pdf".to_string()),
             size: Some(1024),
             date: Some("2021-12-20".to_string()),
+            file_count: None,
+            file_type: None,
+            category: None,
         }];
 
-        let jsx = tree_to_jsx(&nodes, 1);
+        let jsx = tree_to_jsx(&nodes, 1, &FileTreeConfig::default());
         assert!(jsx.contains("<File"));
         assert!(jsx.contains("name=\"test.pdf\""));
         assert!(jsx.contains("url=\"https://example.com/test.pdf\""));
@@ -345,13 +527,19 @@ mod tests {
                 url: Some("https://example.com/file.txt".to_string()),
                 size: Some(100),
                 date: None,
+                file_count: None,
+                file_type: None,
+                category: None,
             }],
             url: None,
             size: None,
             date: None,
+            file_count: None,
+            file_type: None,
+            category: None,
         }];
 
-        let jsx = tree_to_jsx(&nodes, 1);
+        let jsx = tree_to_jsx(&nodes, 1, &FileTreeConfig::default());
         assert!(jsx.contains("<Folder name=\"docs\">"));
         assert!(jsx.contains("</Folder>"));
         assert!(jsx.contains("<File name=\"file.txt\""));
@@ -366,9 +554,12 @@ mod tests {
             url: Some("https://example.com/empty.txt".to_string()),
             size: Some(0),
             date: None,
+            file_count: None,
+            file_type: None,
+            category: None,
         }];
 
-        let jsx = tree_to_jsx(&nodes, 1);
+        let jsx = tree_to_jsx(&nodes, 1, &FileTreeConfig::default());
         // Size should be excluded if 0
         assert!(!jsx.contains("size="));
     }
@@ -388,17 +579,26 @@ mod tests {
                     url: Some("https://example.com/file.txt".to_string()),
                     size: Some(100),
                     date: None,
+                    file_count: None,
+                    file_type: None,
+                    category: None,
                 }],
                 url: None,
                 size: None,
                 date: None,
+                file_count: None,
+                file_type: None,
+                category: None,
             }],
             url: None,
             size: None,
             date: None,
+            file_count: None,
+            file_type: None,
+            category: None,
         }];
 
-        let jsx = tree_to_jsx(&nodes, 1);
+        let jsx = tree_to_jsx(&nodes, 1, &FileTreeConfig::default());
         // Check proper indentation
         assert!(jsx.contains("  <Folder name=\"folder\">"));
         assert!(jsx.contains("    <Folder name=\"nested\">"));
@@ -408,7 +608,211 @@ mod tests {
     #[test]
     fn test_tree_to_jsx_empty() {
         let nodes: Vec<FileNode> = vec![];
-        let jsx = tree_to_jsx(&nodes, 1);
+        let jsx = tree_to_jsx(&nodes, 1, &FileTreeConfig::default());
         assert_eq!(jsx, "");
     }
+
+    #[test]
+    fn test_build_tree_classifies_files() {
+        let mut data = HashMap::new();
+        data.insert(
+            "课件/第二章习题.pdf".to_string(),
+            FileMetadata {
+                size: Some(100),
+                time: None,
+            },
+        );
+        data.insert(
+            "exams/2022.zip".to_string(),
+            FileMetadata {
+                size: Some(100),
+                time: None,
+            },
+        );
+
+        let tree = build_file_tree(&WorktreeData(data), "test-repo");
+        let exams = &tree[0];
+        assert_eq!((exams.file_type, exams.category), (None, None));
+        assert_eq!(exams.children[0].file_type, Some(FileType::Archive));
+        assert_eq!(exams.children[0].category, Some(Category::Exam));
+
+        let jsx = tree_to_jsx(&tree, 1, &FileTreeConfig::default());
+        assert!(jsx.contains("name=\"2022.zip\""));
+        assert!(jsx.contains("size={100} type=\"archive\" category=\"exam\" />"));
+        assert!(jsx.contains("type=\"pdf\" category=\"homework\" />"));
+    }
+
+    #[test]
+    fn test_category_view_jsx() {
+        let mut data = HashMap::new();
+        for path in ["hw/hw1.pdf", "slides/l1.pptx", "hw/hw2.pdf", "misc.txt"] {
+            data.insert(
+                path.to_string(),
+                FileMetadata {
+                    size: None,
+                    time: None,
+                },
+            );
+        }
+
+        let tree = build_file_tree(&WorktreeData(data), "test-repo");
+        let jsx = category_view_jsx(&tree, 1);
+        let lines: Vec<&str> = jsx.lines().collect();
+
+        assert_eq!(lines[0], "  <Folder name=\"作业\">");
+        assert!(lines[1].starts_with("    <File name=\"hw/hw1.pdf\""));
+        assert!(lines[2].starts_with("    <File name=\"hw/hw2.pdf\""));
+        assert_eq!(lines[4], "  <Folder name=\"课件\">");
+        assert_eq!(lines[7], "  <Folder name=\"其他\">");
+        assert!(!jsx.contains("试卷"));
+        assert_eq!(category_view_jsx(&[], 1), "");
+    }
+
+    #[test]
+    fn test_folder_aggregates() {
+        let mut data = HashMap::new();
+        for (path, size, time) in [
+            ("docs/a.pdf", Some(100), Some(1640000000)),
+            ("docs/sub/b.pdf", Some(200), Some(1650000000)),
+            ("docs/sub/c.pdf", None, None),
+        ] {
+            data.insert(path.to_string(), FileMetadata { size, time });
+        }
+
+        let tree = build_file_tree(&WorktreeData(data), "test-repo");
+        let docs = &tree[0];
+        assert_eq!(docs.file_count, Some(3));
+        assert_eq!(docs.size, Some(300));
+        assert_eq!(docs.date.as_deref(), Some("2022-04-15"));
+        let sub = &docs.children[0];
+        assert_eq!((sub.file_count, sub.size), (Some(2), Some(200)));
+        assert_eq!(docs.children[1].file_count, None);
+
+        let jsx = tree_to_jsx(&tree, 1, &FileTreeConfig::default());
+        assert!(
+            jsx.contains("  <Folder name=\"docs\" fileCount={3} size={300} date=\"2022-04-15\">")
+        );
+    }
+
+    #[test]
+    fn test_tree_natural_sorting() {
+        let mut data = HashMap::new();
+        for path in [
+            "lecture10.pdf",
+            "lecture2.pdf",
+            "Lecture1.pdf",
+            "第十章/a.pdf",
+            "第二章/a.pdf",
+        ] {
+            data.insert(
+                path.to_string(),
+                FileMetadata {
+                    size: None,
+                    time: None,
+                },
+            );
+        }
+
+        let tree = build_file_tree(&WorktreeData(data), "test-repo");
+        let names: Vec<&str> = tree.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "第二章",
+                "第十章",
+                "Lecture1.pdf",
+                "lecture2.pdf",
+                "lecture10.pdf"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sort_tree_by_date_and_size() {
+        let mut data = HashMap::new();
+        for (path, size, time) in [
+            ("old/a.pdf", Some(500), Some(1600000000)),
+            ("new/a.pdf", Some(100), Some(1700000000)),
+            ("small.pdf", Some(10), Some(1650000000)),
+            ("large.pdf", Some(900), Some(1640000000)),
+            ("unknown.pdf", None, None),
+        ] {
+            data.insert(path.to_string(), FileMetadata { size, time });
+        }
+        let names =
+            |nodes: &[FileNode]| -> Vec<String> { nodes.iter().map(|n| n.name.clone()).collect() };
+
+        let mut tree = build_file_tree(&WorktreeData(data), "test-repo");
+        sort_tree(&mut tree, FileOrder::Date);
+        assert_eq!(
+            names(&tree),
+            vec!["new", "old", "small.pdf", "large.pdf", "unknown.pdf"]
+        );
+
+        sort_tree(&mut tree, FileOrder::Size);
+        assert_eq!(
+            names(&tree),
+            vec!["old", "new", "large.pdf", "small.pdf", "unknown.pdf"]
+        );
+
+        sort_tree(&mut tree, FileOrder::Name);
+        assert_eq!(
+            names(&tree),
+            vec!["new", "old", "large.pdf", "small.pdf", "unknown.pdf"]
+        );
+    }
+
+    #[test]
+    fn test_tree_to_jsx_collapse_rules() {
+        let mut data = HashMap::new();
+        for path in ["big/1.pdf", "big/2.pdf", "big/3.pdf", "a/b/c/d.pdf"] {
+            data.insert(
+                path.to_string(),
+                FileMetadata {
+                    size: None,
+                    time: None,
+                },
+            );
+        }
+        let tree = build_file_tree(&WorktreeData(data), "test-repo");
+
+        let config = FileTreeConfig {
+            collapse_files: Some(2),
+            collapse_depth: Some(2),
+            ..FileTreeConfig::default()
+        };
+        let jsx = tree_to_jsx(&tree, 1, &config);
+        assert!(jsx.contains("<Folder name=\"a\" fileCount={1} defaultOpen>"));
+        assert!(jsx.contains("<Folder name=\"b\" fileCount={1} defaultOpen>"));
+        assert!(jsx.contains("<Folder name=\"c\" fileCount={1}>"));
+        assert!(jsx.contains("<Folder name=\"big\" fileCount={3}>"));
+
+        // Without rules folders keep the component default
+        let jsx = tree_to_jsx(&tree, 1, &FileTreeConfig::default());
+        assert!(!jsx.contains("defaultOpen"));
+    }
+
+    #[test]
+    fn test_summarize_files() {
+        let mut data = HashMap::new();
+        for (path, size) in [
+            ("a.pdf", Some(100)),
+            ("docs/b.pdf", Some(200)),
+            ("docs/c.pdf", None),
+        ] {
+            data.insert(path.to_string(), FileMetadata { size, time: None });
+        }
+
+        let tree = build_file_tree(&WorktreeData(data), "test-repo");
+        assert_eq!(summarize_files(&tree), (3, 300));
+        assert_eq!(summarize_files(&[]), (0, 0));
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MB");
+    }
 }
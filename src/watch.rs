@@ -0,0 +1,517 @@
+//! Watch mode for local authoring.
+//!
+//! The `watch` command monitors `repos/`, `hoa-major-data/plans/` and
+//! `hoa-major-data/grades_summary.json`. Each batch of changes only rewrites the
+//! pages it affects: a changed README, worktree or `.hoaignore` rewrites the
+//! course page and the plans including the course, a changed plan TOML or grades
+//! summary rewrites the plans whose merged data differ. Pages of removed plans and
+//! courses are deleted. Cross-plan pages and JSON are refreshed after plan
+//! changes, and the catalogue, search and resources indexes after changes to the
+//! plans or to a course they include. File trees are only reloaded for changed
+//! worktrees.
+
+use crate::assets::AssetMirror;
+use crate::config::Config;
+use crate::error::{FumaError, Result};
+use crate::generator::{self, PageUpdate, UpdatedPages};
+use crate::ignore::{IgnoreRules, IGNORE_EXTENSION};
+use crate::models::Plan;
+use crate::tree::{FileTree, FileTrees};
+use crate::{api, catalogue, compare, formatter, loader, majors, resources, search};
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Time to wait for related events (e.g. an editor's save sequence) to arrive
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Grades summary merged into the plans
+const GRADES_SUMMARY_FILE: &str = "grades_summary.json";
+
+/// Input changed on disk
+#[derive(Debug, PartialEq)]
+enum Change {
+    /// README of a course
+    Readme(String),
+    /// Worktree or exclusion rules of a course
+    Worktree(String),
+    /// A plan TOML or the grades summary
+    Plans,
+}
+
+/// Inputs changed by a batch of events
+#[derive(Debug, Default)]
+struct Changes {
+    readmes: BTreeSet<String>,
+    worktrees: BTreeSet<String>,
+    plans: bool,
+}
+
+impl Changes {
+    fn add(&mut self, change: Change) {
+        match change {
+            Change::Readme(code) => {
+                self.readmes.insert(code);
+            }
+            Change::Worktree(code) => {
+                self.worktrees.insert(code);
+            }
+            Change::Plans => self.plans = true,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.readmes.is_empty() && self.worktrees.is_empty() && !self.plans
+    }
+
+    /// Codes of the courses whose README or worktree changed
+    fn courses(&self) -> BTreeSet<&str> {
+        self.readmes
+            .iter()
+            .chain(&self.worktrees)
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+/// Classify a changed path, ignoring files that do not feed the generator
+fn classify(path: &Path, repos_dir: &Path, data_dir: &Path) -> Option<Change> {
+    let extension = path.extension().and_then(|e| e.to_str());
+
+    if path.parent() == Some(repos_dir) {
+        let code = path.file_stem()?.to_str()?.to_string();
+        return match extension? {
+            "mdx" => Some(Change::Readme(code)),
+            "json" => Some(Change::Worktree(code)),
+            ext if ext == IGNORE_EXTENSION => Some(Change::Worktree(code)),
+            _ => None,
+        };
+    }
+
+    if (path.starts_with(data_dir.join("plans")) && extension == Some("toml"))
+        || path == data_dir.join(GRADES_SUMMARY_FILE)
+    {
+        return Some(Change::Plans);
+    }
+
+    None
+}
+
+/// Keys of plans that were added, changed or removed
+fn changed_plans(old: &[Plan], new: &[Plan]) -> Result<BTreeSet<(String, String)>> {
+    let key = |plan: &Plan| (plan.year.clone(), plan.major_code.clone());
+
+    let mut old_values = HashMap::new();
+    for plan in old {
+        old_values.insert(key(plan), serde_json::to_value(plan)?);
+    }
+
+    let mut changed = BTreeSet::new();
+    for plan in new {
+        let value = serde_json::to_value(plan)?;
+        if old_values.remove(&key(plan)).as_ref() != Some(&value) {
+            changed.insert(key(plan));
+        }
+    }
+    changed.extend(old_values.into_keys());
+
+    Ok(changed)
+}
+
+/// Inputs and outputs of the site, with the plans as last loaded
+struct Site {
+    repo_root: PathBuf,
+    repos_dir: PathBuf,
+    data_dir: PathBuf,
+    docs_dir: PathBuf,
+    public_dir: PathBuf,
+    repos_set: HashSet<String>,
+    config: Config,
+    /// Global exclusion rules from the configuration
+    exclude: IgnoreRules,
+    plans: Vec<Plan>,
+    /// File trees as last loaded, reloaded only for changed courses
+    trees: FileTrees,
+    assets: Option<AssetMirror>,
+}
+
+impl Site {
+    /// Rewrite the pages affected by a batch of changes.
+    ///
+    /// The plans as last loaded are kept until every page has been written, so
+    /// a failed batch is compared against them again on the next change.
+    async fn update(&mut self, changes: Changes) -> Result<()> {
+        // File trees of untouched courses are reused from the previous batches
+        for code in &changes.worktrees {
+            reload_tree(&mut self.trees, &self.repos_dir, code, &self.exclude)?;
+        }
+
+        let mut update = PageUpdate::default();
+        let mut new_plans = None;
+        if changes.plans {
+            let plans = loader::load_all_plans(&self.data_dir)?;
+            update.plans = changed_plans(&self.plans, &plans)?;
+            update.previous = self
+                .plans
+                .iter()
+                .filter(|p| {
+                    update
+                        .plans
+                        .contains(&(p.year.clone(), p.major_code.clone()))
+                })
+                .cloned()
+                .collect();
+
+            // Courses new to the plans have no file tree loaded yet
+            let missing: BTreeSet<&str> = plans
+                .iter()
+                .flat_map(|plan| &plan.courses)
+                .map(|course| course.code.as_str())
+                .filter(|code| !self.trees.contains_key(*code))
+                .collect();
+            for code in missing {
+                reload_tree(&mut self.trees, &self.repos_dir, code, &self.exclude)?;
+            }
+
+            if !update.plans.is_empty() {
+                new_plans = Some(plans);
+            }
+        }
+        let plans = new_plans.as_deref().unwrap_or(&self.plans);
+
+        // Only courses of the plans have pages
+        let touched = touched_courses(plans, &changes.courses());
+        update.courses = touched
+            .iter()
+            .filter(|code| self.repos_set.is_empty() || self.repos_set.contains(**code))
+            .map(|code| code.to_string())
+            .collect();
+
+        // The indexes list every course of the plans, with or without a page
+        let indexes_affected = !update.plans.is_empty() || !touched.is_empty();
+        if update.is_empty() && !indexes_affected {
+            println!("No pages affected");
+            return Ok(());
+        }
+
+        let pages = if update.is_empty() {
+            UpdatedPages::default()
+        } else {
+            generator::update_course_pages(
+                plans,
+                &update,
+                &self.repos_dir,
+                &self.docs_dir,
+                &self.repos_set,
+                &self.trees,
+                &self.config,
+                self.assets.as_mut(),
+            )
+            .await?
+        };
+
+        // Cross-plan pages depend on every plan; the course JSON also lists
+        // README and resource state
+        if !update.plans.is_empty() {
+            compare::generate_comparisons(
+                plans,
+                &self.docs_dir,
+                &self.public_dir,
+                &self.config.semesters,
+            )?;
+            majors::generate_major_pages(plans, &self.docs_dir, |code| {
+                generator::has_course_page(code, &self.repos_dir, &self.repos_set)
+            })?;
+            api::generate_api(
+                plans,
+                &self.repos_dir,
+                &self.repos_set,
+                &self.trees,
+                &self.public_dir,
+            )?;
+        } else {
+            api::generate_course_documents(
+                touched.iter().copied(),
+                plans,
+                &self.repos_dir,
+                &self.repos_set,
+                &self.trees,
+                &self.public_dir,
+            )?;
+        }
+
+        if indexes_affected {
+            catalogue::generate_catalogue(
+                plans,
+                &self.repos_dir,
+                &self.repos_set,
+                &self.trees,
+                &self.docs_dir,
+                &self.public_dir,
+            )?;
+            resources::generate_resources_index(
+                plans,
+                &self.repos_dir,
+                &self.repos_set,
+                &self.trees,
+                &self.docs_dir,
+                &self.public_dir,
+            )?;
+            search::generate_search_index(
+                plans,
+                &self.repos_dir,
+                &self.repos_set,
+                &self.trees,
+                &self.public_dir,
+            )?;
+        }
+
+        if let Some(mirror) = &self.assets {
+            mirror.save()?;
+        }
+
+        for path in &pages.written {
+            formatter::format_all_mdx_files(path)?;
+            let shown = path.strip_prefix(&self.repo_root).unwrap_or(path);
+            println!("  ✓ {}", shown.display());
+        }
+        for path in &pages.removed {
+            let shown = path.strip_prefix(&self.repo_root).unwrap_or(path);
+            println!("  ✗ {}", shown.display());
+        }
+        if !update.plans.is_empty() {
+            println!("  ✓ comparisons, major pages and JSON API");
+        }
+        if indexes_affected {
+            println!("  ✓ course catalogue, search and resources indexes");
+        }
+
+        if let Some(plans) = new_plans {
+            self.plans = plans;
+        }
+        Ok(())
+    }
+}
+
+/// Load the file tree of a course again, dropping it if the worktree is gone
+fn reload_tree(
+    trees: &mut FileTrees,
+    repos_dir: &Path,
+    code: &str,
+    exclude: &IgnoreRules,
+) -> Result<()> {
+    match loader::load_worktree(repos_dir, code, exclude)? {
+        Some(worktree) => {
+            trees.insert(code.to_string(), FileTree::new(worktree, code));
+        }
+        None => {
+            trees.remove(code);
+        }
+    }
+    Ok(())
+}
+
+/// The courses included in any of the plans
+fn touched_courses<'a>(plans: &[Plan], courses: &BTreeSet<&'a str>) -> BTreeSet<&'a str> {
+    courses
+        .iter()
+        .copied()
+        .filter(|code| {
+            plans
+                .iter()
+                .any(|plan| plan.courses.iter().any(|course| course.code == *code))
+        })
+        .collect()
+}
+
+/// Run the `watch` command until interrupted.
+///
+/// Pages are only rewritten on change, so a full run should come first.
+pub async fn run(repo_root: &Path, args: &[String]) -> Result<()> {
+    let mut mirror_assets = false;
+    for arg in args {
+        match arg.as_str() {
+            "--mirror-assets" => mirror_assets = true,
+            other => {
+                return Err(FumaError::InvalidArgument(format!(
+                    "unknown watch option: {}",
+                    other
+                )));
+            }
+        }
+    }
+
+    // Events carry absolute paths, so compare against canonical directories
+    let repo_root = repo_root.canonicalize()?;
+    let repos_dir = repo_root.join("repos");
+    let data_dir = repo_root.join("hoa-major-data");
+    let plans_dir = data_dir.join("plans");
+    for dir in [&repos_dir, &plans_dir] {
+        if !dir.exists() {
+            return Err(FumaError::MissingDirectory(dir.clone()));
+        }
+    }
+
+    let public_dir = repo_root.join("public");
+    let config = Config::load(&repo_root)?;
+    let exclude = config.file_tree.exclude_rules();
+    let plans = loader::load_all_plans(&data_dir)?;
+    let mut site = Site {
+        repos_set: loader::load_repos_list(&repo_root)?,
+        trees: loader::load_file_trees(&plans, &repos_dir, &exclude)?,
+        exclude,
+        config,
+        plans,
+        assets: if mirror_assets {
+            Some(AssetMirror::load(&public_dir)?)
+        } else {
+            None
+        },
+        docs_dir: repo_root.join("content/docs"),
+        public_dir,
+        repo_root,
+        repos_dir,
+        data_dir,
+    };
+    println!("Loaded {} training plans", site.plans.len());
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event| {
+        let _ = tx.send(event);
+    })?;
+    watcher.watch(&site.repos_dir, RecursiveMode::NonRecursive)?;
+    watcher.watch(&plans_dir, RecursiveMode::Recursive)?;
+    // The grades summary is watched through its directory so that editors
+    // replacing the file are noticed
+    watcher.watch(&site.data_dir, RecursiveMode::NonRecursive)?;
+
+    println!(
+        "Watching {} and {} for changes (Ctrl+C to stop)",
+        site.repos_dir.display(),
+        site.data_dir.display()
+    );
+
+    while let Some(first) = rx.recv().await {
+        tokio::time::sleep(DEBOUNCE).await;
+        let mut events = vec![first];
+        while let Ok(event) = rx.try_recv() {
+            events.push(event);
+        }
+
+        let mut changes = Changes::default();
+        for event in events {
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => {
+                    eprintln!("Watch error: {}", e);
+                    continue;
+                }
+            };
+            if matches!(event.kind, EventKind::Access(_)) {
+                continue;
+            }
+
+            for path in &event.paths {
+                if let Some(change) = classify(path, &site.repos_dir, &site.data_dir) {
+                    changes.add(change);
+                }
+            }
+        }
+
+        if changes.is_empty() {
+            continue;
+        }
+
+        println!(
+            "\n[{}] Regenerating...",
+            chrono::Local::now().format("%H:%M:%S")
+        );
+        // Keep watching after errors such as a TOML file saved mid-edit
+        if let Err(e) = site.update(changes).await {
+            eprintln!("Error: {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::course;
+
+    fn plan(year: &str, major_code: &str, credit: f64) -> Plan {
+        let math = course("MATH1001").with_name("高等数学").with_credit(credit);
+        crate::models::test_support::plan(year, major_code, vec![math]).with_major_name("计算机")
+    }
+
+    #[test]
+    fn test_classify() {
+        let repos_dir = Path::new("/site/repos");
+        let data_dir = Path::new("/site/hoa-major-data");
+        let classify = |path: &str| classify(Path::new(path), repos_dir, data_dir);
+
+        assert_eq!(
+            classify("/site/repos/MATH1001.mdx"),
+            Some(Change::Readme("MATH1001".to_string()))
+        );
+        assert_eq!(
+            classify("/site/repos/MATH1001.json"),
+            Some(Change::Worktree("MATH1001".to_string()))
+        );
+        assert_eq!(
+            classify("/site/repos/MATH1001.hoaignore"),
+            Some(Change::Worktree("MATH1001".to_string()))
+        );
+        assert_eq!(
+            classify("/site/hoa-major-data/plans/2023_080901.toml"),
+            Some(Change::Plans)
+        );
+        assert_eq!(
+            classify("/site/hoa-major-data/grades_summary.json"),
+            Some(Change::Plans)
+        );
+
+        assert_eq!(classify("/site/repos/.MATH1001.mdx.swp"), None);
+        assert_eq!(classify("/site/repos/nested/MATH1001.mdx"), None);
+        assert_eq!(classify("/site/hoa-major-data/plans/notes.md"), None);
+        assert_eq!(classify("/site/hoa-major-data/other.json"), None);
+    }
+
+    #[test]
+    fn test_touched_courses() {
+        let plans = vec![plan("2023", "080901", 5.0)];
+        let mut changes = Changes::default();
+        assert!(changes.is_empty());
+
+        changes.add(Change::Readme("PHYS1001".to_string()));
+        assert!(touched_courses(&plans, &changes.courses()).is_empty());
+
+        changes.add(Change::Worktree("MATH1001".to_string()));
+        assert_eq!(changes.courses(), BTreeSet::from(["MATH1001", "PHYS1001"]));
+        assert_eq!(
+            touched_courses(&plans, &changes.courses()),
+            BTreeSet::from(["MATH1001"])
+        );
+        assert!(!changes.plans);
+    }
+
+    #[test]
+    fn test_changed_plans() {
+        let old = vec![plan("2023", "080901", 5.0), plan("2023", "080902", 5.0)];
+        let new = vec![plan("2023", "080901", 5.5), plan("2024", "080901", 5.0)];
+
+        let changed = changed_plans(&old, &new).unwrap();
+        let changed: Vec<(&str, &str)> = changed
+            .iter()
+            .map(|(y, m)| (y.as_str(), m.as_str()))
+            .collect();
+        assert_eq!(
+            changed,
+            vec![("2023", "080901"), ("2023", "080902"), ("2024", "080901")]
+        );
+
+        assert!(changed_plans(&old, &old).unwrap().is_empty());
+    }
+}